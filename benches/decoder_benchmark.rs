@@ -1,10 +1,14 @@
 use criterion::*;
 
 use fountaincode::decoder::Decoder;
+use fountaincode::droplet::Droplet;
 use fountaincode::encoder::Encoder;
 use fountaincode::ideal_encoder::IdealEncoder;
+use fountaincode::lt_encoder::LtEncoder;
 use fountaincode::robust_encoder::RobustEncoder;
+use fountaincode::robust_soliton::RobustSoliton;
 use fountaincode::types::*;
+use fountaincode::xor::Kernel;
 use itertools::iproduct;
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
@@ -34,7 +38,7 @@ fn robust_enc_dec_helper(
     loop {
         let drop = enc.next();
         if loss_rng.gen::<f32>() > loss {
-            match dec.catch(drop) {
+            match dec.catch(drop).unwrap() {
                 CatchResult::Missing(_stats) => {
                     //a systematic encoder and no loss on channel should only need k symbols
                     //assert_eq!(stats.cnt_chunks-stats.unknown_chunks, cnt_drops)
@@ -48,6 +52,7 @@ fn robust_enc_dec_helper(
                     }
                     return;
                 }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
             }
         }
     }
@@ -70,7 +75,7 @@ fn ideal_enc_dec_helper(total_len: usize, chunk_len: usize, loss: f32, enc_type:
     loop {
         let drop = enc.next();
         if loss_rng.gen::<f32>() > loss {
-            match dec.catch(drop) {
+            match dec.catch(drop).unwrap() {
                 CatchResult::Missing(_stats) => {}
                 CatchResult::Finished(data, _stats) => {
                     assert_eq!(to_compare.len(), data.len());
@@ -79,6 +84,7 @@ fn ideal_enc_dec_helper(total_len: usize, chunk_len: usize, loss: f32, enc_type:
                     }
                     return;
                 }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
             }
         }
     }
@@ -124,5 +130,126 @@ fn bench_ideal_vs_robust(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, bench_ideal_vs_robust);
+fn bench_xor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Xor");
+
+    for size in [64, 1024, 65536].iter() {
+        let src = vec![0x5a; *size];
+        let mut dst = vec![0xa5; *size];
+        group.throughput(Throughput::Bytes(*size as u64));
+        for kernel in Kernel::ALL.iter().filter(|kernel| kernel.is_supported()) {
+            group.bench_with_input(
+                BenchmarkId::new(format!("{:?}", kernel), size),
+                size,
+                |b, _| b.iter(|| kernel.xor_into(black_box(&mut dst), black_box(&src))),
+            );
+        }
+    }
+    group.finish();
+}
+
+fn bench_throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Throughput");
+    group.sample_size(20);
+
+    let len = 1 << 20;
+    let buf: Vec<u8> = (0..len).map(|_| thread_rng().gen()).collect();
+    for chunk in [256, 1024, 4096].iter() {
+        let cnt_blocks = len / chunk;
+        let new_encoder = || {
+            RobustEncoder::with_seed(buf.clone(), *chunk, EncoderType::Random, 0.2, None, 0.05, 1)
+        };
+        group.throughput(Throughput::Bytes(len as u64));
+
+        // the droplets of a random encoder until the object is decoded
+        let mut enc = new_encoder();
+        let mut dec = Decoder::new(len, *chunk);
+        let mut drops = Vec::new();
+        loop {
+            let drop = enc.next();
+            drops.push(drop.clone());
+            if let CatchResult::Finished(_, _) = dec.catch(drop).unwrap() {
+                break;
+            }
+        }
+
+        group.bench_with_input(BenchmarkId::new("Encode", chunk), chunk, |b, _| {
+            b.iter_batched(
+                new_encoder,
+                |mut enc| {
+                    for _ in 0..cnt_blocks {
+                        black_box(enc.next());
+                    }
+                },
+                BatchSize::LargeInput,
+            )
+        });
+        #[cfg(feature = "parallel")]
+        group.bench_with_input(BenchmarkId::new("EncodeParallel", chunk), chunk, |b, _| {
+            let enc = new_encoder();
+            b.iter(|| black_box(enc.par_encode_range(0..cnt_blocks)))
+        });
+        group.bench_with_input(BenchmarkId::new("Decode", chunk), chunk, |b, _| {
+            b.iter_batched(
+                || (drops.clone(), Decoder::new(len, *chunk)),
+                |(drops, mut dec)| {
+                    for drop in drops {
+                        dec.catch(drop).unwrap();
+                    }
+                    dec
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn decode_all(drops: &[Droplet], len: usize, blocksize: usize, parallel: bool) -> Vec<u8> {
+    let mut dec = Decoder::new(len, blocksize);
+    #[cfg(feature = "parallel")]
+    dec.set_parallel(parallel).unwrap();
+    #[cfg(not(feature = "parallel"))]
+    let _ = parallel;
+    for drop in drops {
+        if let CatchResult::Finished(data, _) = dec.catch(drop.clone()).unwrap() {
+            return data;
+        }
+    }
+    panic!("the droplets do not suffice");
+}
+
+fn bench_large_objects(c: &mut Criterion) {
+    let mut group = c.benchmark_group("LargeK");
+    group.sample_size(10);
+
+    let blocksize = 64;
+    for k in [10_000, 100_000, 1_000_000].iter() {
+        let len = k * blocksize;
+        let buf: Vec<u8> = (0..len).map(|_| thread_rng().gen()).collect();
+        let mut enc = LtEncoder::builder(blocksize)
+            .seed(1)
+            .distribution(|k, seed| RobustSoliton::new(k, seed, 0.05, None, 0.5))
+            .build(buf);
+        let drops = enc.encode_range(0..k + k / 2).unwrap();
+        group.throughput(Throughput::Bytes(len as u64));
+
+        group.bench_with_input(BenchmarkId::new("Decode", k), k, |b, _| {
+            b.iter(|| decode_all(&drops, len, blocksize, false))
+        });
+        #[cfg(feature = "parallel")]
+        group.bench_with_input(BenchmarkId::new("DecodeParallel", k), k, |b, _| {
+            b.iter(|| decode_all(&drops, len, blocksize, true))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_ideal_vs_robust,
+    bench_xor,
+    bench_throughput,
+    bench_large_objects
+);
 criterion_main!(benches);
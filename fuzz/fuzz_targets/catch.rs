@@ -0,0 +1,41 @@
+#![no_main]
+
+use arbitrary::Arbitrary;
+use fountaincode::decoder::Decoder;
+use fountaincode::droplet::Droplet;
+use fountaincode::types::{DecoderMode, DropType};
+use libfuzzer_sys::fuzz_target;
+
+#[derive(Debug, Arbitrary)]
+enum Kind {
+    Seeded(u64, usize),
+    Edges(usize),
+    Esi(u32),
+}
+
+#[derive(Debug, Arbitrary)]
+struct Input {
+    len: u16,
+    blocksize: u8,
+    elimination: bool,
+    drops: Vec<(Kind, Vec<u8>)>,
+}
+
+// Catches arbitrary droplets, the decoder must reject the invalid ones without panicking
+fuzz_target!(|input: Input| {
+    let mode = if input.elimination {
+        DecoderMode::Elimination
+    } else {
+        DecoderMode::Peeling
+    };
+    let blocksize = usize::from(input.blocksize).max(1);
+    let mut dec = Decoder::with_mode(usize::from(input.len), blocksize, mode);
+    for (kind, data) in input.drops {
+        let droptype = match kind {
+            Kind::Seeded(seed, degree) => DropType::Seeded(seed, degree),
+            Kind::Edges(idx) => DropType::Edges(idx),
+            Kind::Esi(esi) => DropType::Esi(esi),
+        };
+        let _ = dec.catch(Droplet::new(droptype, data));
+    }
+});
@@ -0,0 +1,23 @@
+#![no_main]
+
+use fountaincode::decoder::Decoder;
+use fountaincode::droplet::Droplet;
+use fountaincode::types::DecoderMode;
+use fountaincode::wire::Limits;
+use libfuzzer_sys::fuzz_target;
+
+// Decodes the object announced by the first packet from arbitrary packets
+fuzz_target!(|packets: Vec<Vec<u8>>| {
+    let descriptor = match packets.first().map(|packet| Droplet::from_bytes(packet)) {
+        Some(Ok((_, params))) => params.descriptor(),
+        _ => return,
+    };
+    let mut dec =
+        match Decoder::from_descriptor(&descriptor, DecoderMode::Peeling, Limits::default()) {
+            Ok(dec) => dec,
+            Err(_) => return,
+        };
+    for packet in packets.iter() {
+        let _ = dec.catch_packet(packet);
+    }
+});
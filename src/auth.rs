@@ -0,0 +1,47 @@
+//! Authentication of droplets with HMAC-SHA256.
+//!
+//! Sender and receiver share a key per session. Authenticated packets carry
+//! a tag over the header and the payload, and a receiver holding the key
+//! drops every packet without a valid tag before it reaches the decoder.
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Size of the tag of an authenticated packet
+pub const TAG_LEN: usize = 32;
+
+/// Secret key shared by the sender and the receivers of a session
+#[derive(Clone)]
+pub struct AuthKey {
+    key: Vec<u8>,
+}
+
+impl AuthKey {
+    pub fn new(key: &[u8]) -> AuthKey {
+        AuthKey { key: key.to_vec() }
+    }
+
+    fn mac(&self, header: &[u8], payload: &[u8]) -> Hmac<Sha256> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(&self.key).expect("HMAC accepts keys of any size");
+        mac.update(header);
+        mac.update(payload);
+        mac
+    }
+
+    pub(crate) fn tag(&self, header: &[u8], payload: &[u8]) -> [u8; TAG_LEN] {
+        self.mac(header, payload).finalize().into_bytes().into()
+    }
+
+    /// Compares in constant time
+    pub(crate) fn verify(&self, header: &[u8], payload: &[u8], tag: &[u8]) -> bool {
+        self.mac(header, payload).verify_slice(tag).is_ok()
+    }
+}
+
+/// The key is not printed
+impl std::fmt::Debug for AuthKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AuthKey(..)")
+    }
+}
@@ -0,0 +1,731 @@
+//! Encodes files into droplets and decodes them back.
+//!
+//! Droplet files hold one or more packets in the `wire` format, each one
+//! prefixed with its length as a big-endian u32.
+
+extern crate fountaincode;
+
+use fountaincode::{
+    auth::TAG_LEN,
+    custom_distribution::CustomDistribution,
+    decoder::{DecodeError, Decoder},
+    digest::{digest, Digest},
+    droplet::Droplet,
+    encoder::Encoder,
+    ideal_encoder::IdealEncoder,
+    lt_encoder::LtEncoder,
+    optimizer::{optimize_custom, optimize_robust, Objective, Preset, Simulation},
+    robust_encoder::RobustEncoder,
+    storage::SeekStore,
+    types::{DecoderMode, DropType, EncoderType},
+    wire::{Limits, ObjectParams, WireError, FLAG_CRC32C, HEADER_LEN},
+};
+use std::{
+    convert::TryFrom,
+    env,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+const USAGE: &str = "\
+usage:
+    fountain encode [options] <input> <output>
+    fountain decode [options] <output> <droplets>...
+    fountain inspect <droplets>...
+    fountain optimize [options]
+
+encode writes the droplets of <input> into the stream file <output>,
+or into one file per droplet in the directory <output> with --split.
+
+encode options:
+    --blocksize <bytes>          size of a block, default 1024, at most 1048576
+    --distribution <name>        ideal or robust, default robust
+    --preset <file>              distribution written by optimize, replaces --distribution
+    --overhead <fraction>        droplets beyond the number of blocks, default 0.5
+    --systematic                 send the source blocks first
+    --seed <u64>                 seed of the droplet stream, random by default
+    --object-id <u64>            object id in the packet headers, default 0
+    --split                      write one file per droplet
+    --checksum                   protect every droplet with a CRC32C
+
+decode reads droplets from stream files, droplet files or directories
+holding them, and writes the recovered object to <output>. The object is
+the one three droplets agree on, or the first droplet with a checksum.
+Objects beyond 1 GiB or 2^20 blocks are skipped.
+
+decode options:
+    --elimination                solve with Gaussian elimination when peeling stalls
+    --sha256 <hex>               digest printed by encode, the output is checked against it
+
+optimize simulates the decoder and searches the robust soliton parameters,
+or improves a custom distribution, for the lowest overhead.
+
+optimize options:
+    --blocks <k>                 number of blocks, default 100
+    --loss <fraction>            fraction of lost droplets, default 0
+    --trials <n>                 decodes per candidate, default 100
+    --objective <name>           mean or p95, default mean
+    --elimination                simulate the decoder with Gaussian elimination
+    --seed <u64>                 seed of the simulation, default 0
+    --custom <rounds>            improve the best robust soliton as a custom distribution
+    --preset <file>              write the best distribution to <file> for encode --preset
+";
+
+type Result<T> = std::result::Result<T, String>;
+
+/// Largest block size, it bounds the packets read from droplet files
+const MAX_BLOCKSIZE: usize = 1 << 20;
+
+/// Largest packet of a droplet file, a header with a tag and a block
+const MAX_PACKET_LEN: usize = HEADER_LEN + TAG_LEN + MAX_BLOCKSIZE;
+
+/// Number of droplets which have to agree on the object before it is decoded,
+/// one with a checksum is enough
+const AGREEMENT: usize = 3;
+
+/// Droplets held back until they agree, the most common object is decoded then
+const MAX_PENDING: usize = 16;
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("encode") => encode(&args[1..]),
+        Some("decode") => decode(&args[1..]),
+        Some("inspect") => inspect(&args[1..]),
+        Some("optimize") => optimize(&args[1..]),
+        Some("-h") | Some("--help") | Some("help") => {
+            print!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("expected a command\n\n{}", USAGE)),
+    };
+    if let Err(msg) = result {
+        eprintln!("fountain: {}", msg);
+        process::exit(1);
+    }
+}
+
+/// Splits the arguments into options with their values and positional arguments
+struct Args {
+    options: Vec<(String, Option<String>)>,
+    positional: Vec<String>,
+}
+
+impl Args {
+    /// `flags` take no value, every other option takes one
+    fn parse(args: &[String], flags: &[&str], valued: &[&str]) -> Result<Args> {
+        let mut options = Vec::new();
+        let mut positional = Vec::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if !arg.starts_with("--") {
+                positional.push(arg.clone());
+            } else if flags.contains(&arg.as_str()) {
+                options.push((arg.clone(), None));
+            } else if valued.contains(&arg.as_str()) {
+                let value = iter
+                    .next()
+                    .ok_or_else(|| format!("{} needs a value", arg))?;
+                options.push((arg.clone(), Some(value.clone())));
+            } else {
+                return Err(format!("unknown option {}", arg));
+            }
+        }
+        Ok(Args {
+            options,
+            positional,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.iter().any(|(opt, _)| opt == name)
+    }
+
+    fn value<T: std::str::FromStr>(&self, name: &str) -> Result<Option<T>> {
+        match self.options.iter().rev().find(|(opt, _)| opt == name) {
+            Some((_, Some(value))) => value
+                .parse()
+                .map(Some)
+                .map_err(|_| format!("invalid value {} for {}", value, name)),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn encode(args: &[String]) -> Result<()> {
+    let args = Args::parse(
+        args,
+        &["--systematic", "--split", "--checksum"],
+        &[
+            "--blocksize",
+            "--distribution",
+            "--preset",
+            "--overhead",
+            "--seed",
+            "--object-id",
+        ],
+    )?;
+    let (input, output) = match args.positional.as_slice() {
+        [input, output] => (input, output),
+        _ => return Err(format!("encode takes an input and an output\n\n{}", USAGE)),
+    };
+
+    let blocksize: usize = args.value("--blocksize")?.unwrap_or(1024);
+    let overhead: f64 = args.value("--overhead")?.unwrap_or(0.5);
+    let distribution: String = args
+        .value("--distribution")?
+        .unwrap_or_else(|| "robust".to_string());
+    let seed: u64 = args.value("--seed")?.unwrap_or_else(rand::random);
+    let object_id: u64 = args.value("--object-id")?.unwrap_or(0);
+    let encodertype = if args.flag("--systematic") {
+        EncoderType::Systematic
+    } else {
+        EncoderType::Random
+    };
+    if blocksize == 0 || blocksize > MAX_BLOCKSIZE {
+        return Err(format!(
+            "the block size must be between 1 and {} bytes",
+            MAX_BLOCKSIZE
+        ));
+    }
+    if overhead.is_nan() || overhead < 0.0 {
+        return Err("the overhead must not be negative".to_string());
+    }
+
+    let data = fs::read(input).map_err(|e| format!("reading {}: {}", input, e))?;
+    if data.is_empty() {
+        return Err(format!("{} is empty", input));
+    }
+    let sha256 = digest(&data);
+    let params = ObjectParams::new(data.len(), blocksize).with_object_id(object_id);
+    if Limits::default().check(&params).is_err() {
+        return Err(format!(
+            "{} is too large to decode, at most 1 GiB in 2^20 blocks",
+            input
+        ));
+    }
+    let cnt_droplets = (params.cnt_blocks() as f64 * (1.0 + overhead)).ceil() as usize;
+
+    let preset = match args.value::<String>("--preset")? {
+        Some(path) => Some(read_preset(&path)?),
+        None => None,
+    };
+
+    let mut enc: Box<dyn Encoder> = match (preset, distribution.as_str()) {
+        (Some(Preset::Robust(robust)), _) => Box::new(RobustEncoder::with_seed(
+            data,
+            blocksize,
+            encodertype,
+            robust.c,
+            robust.spike,
+            robust.delta,
+            seed,
+        )),
+        (Some(Preset::Custom(probabilities)), _) => {
+            let dist = CustomDistribution::new(&probabilities, seed).map_err(|e| e.to_string())?;
+            Box::new(
+                LtEncoder::builder(blocksize)
+                    .encodertype(encodertype)
+                    .seed(seed)
+                    .distribution(|_, _| dist)
+                    .build(data),
+            )
+        }
+        (None, "ideal") => Box::new(IdealEncoder::with_seed(data, blocksize, encodertype, seed)),
+        (None, "robust") => Box::new(RobustEncoder::with_seed(
+            data,
+            blocksize,
+            encodertype,
+            0.2,
+            None,
+            0.05,
+            seed,
+        )),
+        (None, other) => return Err(format!("unknown distribution {}", other)),
+    };
+
+    let serialize: Serialize = if args.flag("--checksum") {
+        Droplet::to_bytes_with_checksum
+    } else {
+        Droplet::to_bytes
+    };
+    let packets = (0..cnt_droplets).map(|_| Ok(serialize(&enc.try_next()?, &params)));
+    let written = if args.flag("--split") {
+        write_split(output, packets)
+    } else {
+        write_stream(output, packets)
+    };
+    written.map_err(|e| format!("writing {}: {}", output, e))?;
+
+    eprintln!(
+        "{} blocks of {} bytes, {} droplets written to {}",
+        params.cnt_blocks(),
+        blocksize,
+        cnt_droplets,
+        output
+    );
+    println!("sha256 {}", to_hex(&sha256));
+    Ok(())
+}
+
+/// Reads the first line of a preset file which is neither empty nor a comment
+fn read_preset(path: &str) -> Result<Preset> {
+    let text = fs::read_to_string(path).map_err(|e| format!("reading {}: {}", path, e))?;
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .ok_or_else(|| format!("{} holds no preset", path))?;
+    line.parse().map_err(|e| format!("{}: {}", path, e))
+}
+
+type Serialize = fn(&Droplet, &ObjectParams) -> Vec<u8>;
+
+fn to_hex(digest: &Digest) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Digest> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut digest = [0; 32];
+    for (i, b) in digest.iter_mut().enumerate() {
+        *b = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(digest)
+}
+
+fn write_packet<W: Write>(writer: &mut W, packet: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(packet.len()).expect("packet does not fit into u32");
+    writer.write_all(&len.to_be_bytes())?;
+    writer.write_all(packet)
+}
+
+fn write_stream<I: Iterator<Item = io::Result<Vec<u8>>>>(
+    output: &str,
+    packets: I,
+) -> io::Result<()> {
+    let mut writer = BufWriter::new(File::create(output)?);
+    for packet in packets {
+        write_packet(&mut writer, &packet?)?;
+    }
+    writer.flush()
+}
+
+fn write_split<I: Iterator<Item = io::Result<Vec<u8>>>>(
+    output: &str,
+    packets: I,
+) -> io::Result<()> {
+    fs::create_dir_all(output)?;
+    for (i, packet) in packets.enumerate() {
+        let path = Path::new(output).join(format!("droplet-{:06}.bin", i));
+        let mut writer = BufWriter::new(File::create(path)?);
+        write_packet(&mut writer, &packet?)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Expands directories into the files they contain, in name order
+fn droplet_files(inputs: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for input in inputs {
+        let path = PathBuf::from(input);
+        if path.is_dir() {
+            let mut entries: Vec<PathBuf> = fs::read_dir(&path)
+                .and_then(|dir| dir.map(|entry| entry.map(|e| e.path())).collect())
+                .map_err(|e: io::Error| format!("reading {}: {}", input, e))?;
+            entries.retain(|p| p.is_file());
+            entries.sort();
+            files.extend(entries);
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+/// Reads the length prefixed packets of a droplet file one by one.
+///
+/// A packet longer than any droplet, or one cut off by the end of the file,
+/// ends the file with a warning: the packets after it can not be found.
+struct Packets {
+    reader: BufReader<File>,
+    path: PathBuf,
+}
+
+fn read_packets(path: &Path) -> Result<Packets> {
+    let file = File::open(path).map_err(|e| format!("reading {}: {}", path.display(), e))?;
+    Ok(Packets {
+        reader: BufReader::new(file),
+        path: path.to_path_buf(),
+    })
+}
+
+impl Packets {
+    fn read_packet(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.reader.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut len = [0; 4];
+        self.reader.read_exact(&mut len)?;
+        let len = u32::from_be_bytes(len) as usize;
+        if len > MAX_PACKET_LEN {
+            eprintln!(
+                "{} holds a packet of {} bytes, the rest of it is skipped",
+                self.path.display(),
+                len
+            );
+            return Ok(None);
+        }
+        let mut packet = vec![0; len];
+        self.reader.read_exact(&mut packet)?;
+        Ok(Some(packet))
+    }
+}
+
+impl Iterator for Packets {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Result<Vec<u8>>> {
+        match self.read_packet() {
+            Ok(packet) => packet.map(Ok),
+            Err(ref e) if e.kind() == io::ErrorKind::UnexpectedEof => {
+                eprintln!("{} ends with a truncated packet", self.path.display());
+                None
+            }
+            Err(e) => Some(Err(format!("reading {}: {}", self.path.display(), e))),
+        }
+    }
+}
+
+/// Whether a parsed packet carried a CRC32C, which it matched then
+fn has_checksum(packet: &[u8]) -> bool {
+    u16::from_be_bytes([packet[2], packet[3]]) & FLAG_CRC32C != 0
+}
+
+/// The object most of the held back droplets belong to, the first one on a tie
+fn majority(pending: &[(ObjectParams, Droplet)]) -> ObjectParams {
+    let votes = |params: &ObjectParams| pending.iter().filter(|(p, _)| p == params).count();
+    let mut best = pending[0].0;
+    for (params, _) in pending.iter() {
+        if votes(params) > votes(&best) {
+            best = *params;
+        }
+    }
+    best
+}
+
+/// The packets `decode` did not use
+#[derive(Default)]
+struct Skipped {
+    invalid: usize,
+    corrupt: usize,
+    foreign: usize,
+    too_large: usize,
+}
+
+/// Decodes into `output` a single object, the one which the droplets agree on
+struct Object<'a> {
+    output: &'a str,
+    mode: DecoderMode,
+    sha256: Option<Digest>,
+    decoder: Option<(ObjectParams, Decoder<SeekStore<File>>)>,
+    /// the droplets received before the object is known
+    pending: Vec<(ObjectParams, Droplet)>,
+    skipped: Skipped,
+}
+
+impl<'a> Object<'a> {
+    /// Catches a droplet, returns whether the object is complete
+    fn catch(&mut self, params: ObjectParams, drop: Droplet, checked: bool) -> Result<bool> {
+        if self.decoder.is_none() {
+            self.pending.push((params, drop));
+            let votes = self.pending.iter().filter(|(p, _)| *p == params).count();
+            if checked || votes >= AGREEMENT {
+                self.start(params)?;
+            } else if self.pending.len() >= MAX_PENDING {
+                self.start(majority(&self.pending))?;
+            }
+            return Ok(self.is_finished());
+        }
+        let (expected, dec) = self.decoder.as_mut().expect("checked above");
+        if *expected != params {
+            self.skipped.foreign += 1;
+            return Ok(false);
+        }
+        match dec.try_catch(drop) {
+            Ok(_) => {}
+            Err(DecodeError::Io(e)) => return Err(format!("writing {}: {}", self.output, e)),
+            Err(_) => self.skipped.invalid += 1,
+        }
+        Ok(dec.is_finished())
+    }
+
+    /// Creates the decoder of the object described by `params` and catches
+    /// the droplets held back
+    fn start(&mut self, params: ObjectParams) -> Result<()> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(self.output)
+            .map_err(|e| format!("creating {}: {}", self.output, e))?;
+        let mut dec = Decoder::with_store(
+            params.len,
+            params.blocksize,
+            self.mode,
+            SeekStore::new(file),
+        );
+        if let Some(sha256) = self.sha256 {
+            dec.set_digest(sha256);
+        }
+        self.decoder = Some((params, dec));
+        for (params, drop) in std::mem::take(&mut self.pending) {
+            if self.catch(params, drop, false)? {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    /// Decides the object by the droplets held back, if the input ended before
+    fn finish(&mut self) -> Result<()> {
+        if self.decoder.is_none() && !self.pending.is_empty() {
+            self.start(majority(&self.pending))?;
+        }
+        Ok(())
+    }
+
+    fn is_finished(&self) -> bool {
+        match self.decoder {
+            Some((_, ref dec)) => dec.is_finished(),
+            None => false,
+        }
+    }
+}
+
+fn decode(args: &[String]) -> Result<()> {
+    let args = Args::parse(args, &["--elimination"], &["--sha256"])?;
+    let (output, inputs) = match args.positional.split_first() {
+        Some((output, inputs)) if !inputs.is_empty() => (output, inputs),
+        _ => {
+            return Err(format!(
+                "decode takes an output and droplet files\n\n{}",
+                USAGE
+            ))
+        }
+    };
+    let mode = if args.flag("--elimination") {
+        DecoderMode::Elimination
+    } else {
+        DecoderMode::Peeling
+    };
+    let sha256 = match args.value::<String>("--sha256")? {
+        Some(hex) => Some(from_hex(&hex).ok_or_else(|| format!("invalid digest {}", hex))?),
+        None => None,
+    };
+
+    let limits = Limits::default();
+    let mut object = Object {
+        output,
+        mode,
+        sha256,
+        decoder: None,
+        pending: Vec::new(),
+        skipped: Skipped::default(),
+    };
+    'files: for path in droplet_files(inputs)? {
+        for packet in read_packets(&path)? {
+            let packet = packet?;
+            let (drop, params) = match Droplet::from_bytes(&packet) {
+                Ok(parsed) => parsed,
+                Err(WireError::ChecksumMismatch) => {
+                    object.skipped.corrupt += 1;
+                    continue;
+                }
+                Err(_) => {
+                    object.skipped.invalid += 1;
+                    continue;
+                }
+            };
+            if let DropType::Esi(_) = drop.droptype {
+                object.skipped.invalid += 1;
+                continue;
+            }
+            // the decoder holds a block list as long as the object
+            if limits.check(&params).is_err() {
+                object.skipped.too_large += 1;
+                continue;
+            }
+            if object.catch(params, drop, has_checksum(&packet))? {
+                break 'files;
+            }
+        }
+    }
+    object.finish()?;
+
+    let skipped = &object.skipped;
+    if skipped.corrupt > 0 {
+        eprintln!("skipped {} corrupt packets", skipped.corrupt);
+    }
+    if skipped.invalid > 0 {
+        eprintln!("skipped {} invalid packets", skipped.invalid);
+    }
+    if skipped.too_large > 0 {
+        eprintln!(
+            "skipped {} droplets of objects beyond 1 GiB or 2^20 blocks",
+            skipped.too_large
+        );
+    }
+    if skipped.foreign > 0 {
+        eprintln!("skipped {} droplets of other objects", skipped.foreign);
+    }
+    let (params, dec) = object
+        .decoder
+        .ok_or_else(|| "no droplets found".to_string())?;
+    let stats = dec.statistics();
+    if !dec.is_finished() {
+        return Err(format!(
+            "not enough droplets, {} of {} blocks are missing",
+            stats.unknown_chunks, stats.cnt_chunks
+        ));
+    }
+    if dec.is_corrupt() {
+        drop(dec);
+        fs::remove_file(output).map_err(|e| format!("removing {}: {}", output, e))?;
+        return Err(format!(
+            "{} does not match the sha256 digest, a droplet was corrupt",
+            output
+        ));
+    }
+    eprintln!(
+        "decoded {} bytes from {} droplets, overhead {:.3}",
+        params.len, stats.cnt_droplets, stats.overhead
+    );
+    Ok(())
+}
+
+fn inspect(args: &[String]) -> Result<()> {
+    let args = Args::parse(args, &[], &[])?;
+    if args.positional.is_empty() {
+        return Err(format!("inspect takes droplet files\n\n{}", USAGE));
+    }
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+    for path in droplet_files(&args.positional)? {
+        for (i, packet) in read_packets(&path)?.enumerate() {
+            let line = match Droplet::from_bytes(&packet?) {
+                Ok((drop, params)) => {
+                    let kind = match drop.droptype {
+                        DropType::Seeded(seed, degree) => {
+                            format!("seeded seed={:#018x} degree={}", seed, degree)
+                        }
+                        DropType::Edges(idx) => format!("edges block={}", idx),
+                        DropType::Esi(esi) => format!("esi esi={}", esi),
+                    };
+                    format!(
+                        "{}:{} object={} len={} blocksize={} {}",
+                        path.display(),
+                        i,
+                        params.object_id,
+                        params.len,
+                        params.blocksize,
+                        kind
+                    )
+                }
+                Err(e) => format!("{}:{} invalid: {}", path.display(), i, e),
+            };
+            writeln!(out, "{}", line).map_err(|e| e.to_string())?;
+        }
+    }
+    Ok(())
+}
+
+fn optimize(args: &[String]) -> Result<()> {
+    let args = Args::parse(
+        args,
+        &["--elimination"],
+        &[
+            "--blocks",
+            "--loss",
+            "--trials",
+            "--objective",
+            "--seed",
+            "--custom",
+            "--preset",
+        ],
+    )?;
+    if !args.positional.is_empty() {
+        return Err(format!("optimize takes no arguments\n\n{}", USAGE));
+    }
+    let cnt_blocks: usize = args.value("--blocks")?.unwrap_or(100);
+    let loss: f64 = args.value("--loss")?.unwrap_or(0.0);
+    let trials: usize = args.value("--trials")?.unwrap_or(100);
+    let seed: u64 = args.value("--seed")?.unwrap_or(0);
+    let objective = match args.value::<String>("--objective")?.as_deref() {
+        None | Some("mean") => Objective::Mean,
+        Some("p95") => Objective::P95,
+        Some(other) => return Err(format!("unknown objective {}", other)),
+    };
+    let mode = if args.flag("--elimination") {
+        DecoderMode::Elimination
+    } else {
+        DecoderMode::Peeling
+    };
+    if cnt_blocks == 0 {
+        return Err("the number of blocks must not be zero".to_string());
+    }
+    if !(0.0..1.0).contains(&loss) {
+        return Err("the loss must be at least 0 and below 1".to_string());
+    }
+
+    let sim = Simulation::new(cnt_blocks, loss)
+        .with_trials(trials)
+        .with_mode(mode)
+        .with_seed(seed);
+    let candidates = optimize_robust(&sim, objective);
+    println!(
+        "{:>6} {:>6} {:>8} {:>8} {:>6}",
+        "c", "delta", "mean", "p95", "failed"
+    );
+    for candidate in &candidates {
+        println!(
+            "{:>6} {:>6} {:>8.4} {:>8.4} {:>6}",
+            candidate.params.c,
+            candidate.params.delta,
+            candidate.overhead.mean,
+            candidate.overhead.p95,
+            candidate.overhead.cnt_failed
+        );
+    }
+
+    let best = &candidates[0];
+    let (preset, overhead) = match args.value::<usize>("--custom")? {
+        Some(rounds) => {
+            let sol = best.params.soliton(cnt_blocks, 0);
+            let initial: Vec<f64> = (0..=cnt_blocks).map(|d| sol.pmf(d)).collect();
+            let custom =
+                optimize_custom(&sim, objective, &initial, rounds).map_err(|e| e.to_string())?;
+            (Preset::Custom(custom.params), custom.overhead)
+        }
+        None => (Preset::Robust(best.params), best.overhead.clone()),
+    };
+    eprintln!(
+        "best: mean overhead {:.4}, p95 overhead {:.4}, {} of {} trials failed",
+        overhead.mean, overhead.p95, overhead.cnt_failed, trials
+    );
+
+    if let Some(path) = args.value::<String>("--preset")? {
+        let text = format!(
+            "# blocks={} loss={} trials={} mean={:.4} p95={:.4}\n{}\n",
+            cnt_blocks, loss, trials, overhead.mean, overhead.p95, preset
+        );
+        fs::write(&path, text).map_err(|e| format!("writing {}: {}", path, e))?;
+    }
+    Ok(())
+}
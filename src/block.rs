@@ -1,20 +1,22 @@
-use crate::droplet::RxDroplet;
-
 #[derive(Clone)]
 pub struct Block {
     pub idx: usize,
-    pub edges: Vec<RxDroplet>,
+    /// the droplets which wait for the block, indices into the decoder's droplets
+    pub edges: Vec<usize>,
     pub begin_at: usize,
     pub is_known: bool,
+    /// the droplet which recovered the block while its payload is deferred
+    pub recovered_by: Option<usize>,
 }
 
 impl Block {
-    pub fn new(idx: usize, edges: Vec<RxDroplet>, begin_at: usize, is_known: bool) -> Block {
+    pub fn new(idx: usize, edges: Vec<usize>, begin_at: usize, is_known: bool) -> Block {
         Block {
             idx,
             edges,
             begin_at,
             is_known,
+            recovered_by: None,
         }
     }
 }
@@ -0,0 +1,141 @@
+use crate::soliton::Soliton;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{error, fmt};
+
+/// Largest difference between the sum of the probabilities and one,
+/// published tables are rounded to a few digits
+const TOLERANCE: f64 = 1e-4;
+
+/// A degree distribution given by a table of probabilities, e.g. the
+/// distribution Omega of Raptor codes.
+///
+/// The decoder does not need to know the distribution, the degree of every
+/// droplet travels with it.
+#[derive(Debug, Clone)]
+pub struct CustomDistribution {
+    cdf: Vec<f64>,
+    rng: StdRng,
+}
+
+/// Errors returned when a table of probabilities is not a distribution
+#[derive(Debug, Clone, PartialEq)]
+pub enum DistributionError {
+    /// There is no degree with a positive probability
+    Empty,
+    /// Degree zero has a positive probability
+    DegreeZero,
+    /// The probability of a degree is negative or not finite
+    InvalidProbability { degree: usize, probability: f64 },
+    /// The probabilities do not sum up to one
+    NotNormalized(f64),
+}
+
+impl fmt::Display for DistributionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DistributionError::Empty => write!(f, "no degree has a positive probability"),
+            DistributionError::DegreeZero => write!(f, "degree 0 must have probability 0"),
+            DistributionError::InvalidProbability {
+                degree,
+                probability,
+            } => write!(
+                f,
+                "invalid probability {} for degree {}",
+                probability, degree
+            ),
+            DistributionError::NotNormalized(sum) => {
+                write!(f, "probabilities sum up to {}, not 1", sum)
+            }
+        }
+    }
+}
+
+impl error::Error for DistributionError {}
+
+impl CustomDistribution {
+    /// Creates the distribution where degree `d` has probability `probabilities[d]`.
+    /// The probabilities must sum up to one, and `probabilities[0]` must be zero.
+    pub fn new(probabilities: &[f64], seed: u64) -> Result<CustomDistribution, DistributionError> {
+        if probabilities.first().is_some_and(|p| *p != 0.0) {
+            return Err(DistributionError::DegreeZero);
+        }
+        let mut cdf = Vec::with_capacity(probabilities.len().saturating_sub(1));
+        let mut sum = 0.0;
+        for (degree, p) in probabilities.iter().enumerate().skip(1) {
+            if !p.is_finite() || *p < 0.0 {
+                return Err(DistributionError::InvalidProbability {
+                    degree,
+                    probability: *p,
+                });
+            }
+            sum += p;
+            cdf.push(sum);
+        }
+        if sum == 0.0 {
+            return Err(DistributionError::Empty);
+        }
+        if (sum - 1.0).abs() > TOLERANCE {
+            return Err(DistributionError::NotNormalized(sum));
+        }
+        // trailing degrees without probability are never drawn
+        while cdf.len() > 1 && cdf[cdf.len() - 2] >= sum {
+            cdf.pop();
+        }
+        Ok(CustomDistribution {
+            cdf,
+            rng: SeedableRng::seed_from_u64(seed),
+        })
+    }
+
+    /// Creates the distribution from pairs of degree and probability,
+    /// the degrees which are not listed have probability zero.
+    pub fn from_pairs(
+        pairs: &[(usize, f64)],
+        seed: u64,
+    ) -> Result<CustomDistribution, DistributionError> {
+        let max_degree = pairs.iter().map(|(d, _)| *d).max().unwrap_or(0);
+        let mut probabilities = vec![0.0; max_degree + 1];
+        for (degree, p) in pairs {
+            probabilities[*degree] += p;
+        }
+        CustomDistribution::new(&probabilities, seed)
+    }
+
+    /// The distribution Omega of Raptor codes, see Shokrollahi, "Raptor Codes", table I
+    pub fn raptor(seed: u64) -> CustomDistribution {
+        CustomDistribution::from_pairs(
+            &[
+                (1, 0.007_969),
+                (2, 0.493_570),
+                (3, 0.166_220),
+                (4, 0.072_646),
+                (5, 0.082_558),
+                (8, 0.056_058),
+                (9, 0.037_229),
+                (19, 0.055_590),
+                (65, 0.025_023),
+                (66, 0.003_135),
+            ],
+            seed,
+        )
+        .expect("the distribution of the paper is normalized")
+    }
+
+    /// The largest degree with a positive probability
+    pub fn max_degree(&self) -> usize {
+        self.cdf.len()
+    }
+}
+
+impl Soliton for CustomDistribution {
+    fn next(&mut self) -> usize {
+        let u = self.rng.gen::<f64>();
+        self.degree(u)
+    }
+
+    fn degree(&self, u: f64) -> usize {
+        // the sum may be slightly below one, rounding errors end in the last degree
+        let idx = self.cdf.partition_point(|p| *p <= u);
+        idx.min(self.cdf.len() - 1) + 1
+    }
+}
@@ -1,21 +1,61 @@
 use crate::{
+    auth::AuthKey,
     block::Block,
+    digest::{Digest, Hasher},
     droplet::{Droplet, RxDroplet},
-    robust_encoder::get_sample_from_rng_by_seed,
-    types::{CatchResult, DropType},
+    lt_encoder::get_sample_from_rng_by_seed,
+    storage::BlockStore,
+    types::{CatchResult, DecoderMode, DropType},
+    wire::{block_len, cnt_blocks, Limits, ObjectDescriptor, WireError},
+    xor::xor_into,
 };
-use rand::distributions::Uniform;
+use std::{error, fmt, io};
 
 /// Decoder for the Luby transform
-pub struct Decoder {
+///
+/// The recovered blocks are written to a `BlockStore`, by default a `Vec<u8>`.
+pub struct Decoder<S = Vec<u8>> {
     total_length: usize,
     blocksize: usize,
     unknown_chunks: usize,
     number_of_chunks: usize,
     cnt_received_drops: usize,
     blocks: Vec<Block>,
+    store: S,
+    /// the auxiliary blocks of an outer code are kept in memory
+    aux: Vec<u8>,
+    mode: DecoderMode,
+    /// the droplets which wait for more than one block, `Block::edges` refers to them
+    droplets: Vec<Slot>,
+    /// the free entries of `droplets`
+    free: Vec<usize>,
+    /// droplets which wait for a single block
+    ripple: Vec<usize>,
+    /// a known block read back from the store
+    scratch: Vec<u8>,
+    /// the payloads are computed by `flush`, see `set_parallel`
+    parallel: bool,
+    /// the blocks recovered since the last `flush`, in the order of their recovery
+    deferred: Vec<usize>,
+    /// the elimination is not tried before this many droplets are received
+    next_elimination: usize,
+    cnt_peeled: usize,
+    cnt_eliminated: usize,
+    cnt_corrupt: usize,
+    cnt_unauthenticated: usize,
+    auth_key: Option<AuthKey>,
+    /// the expected digest of the object
+    digest: Option<Digest>,
+    digest_mismatch: bool,
+}
+
+/// A received droplet, stored once while it waits for its blocks
+struct Slot {
+    edges_idx: Vec<usize>,
+    /// the blocks which got known are XORed out already
     data: Vec<u8>,
-    dist: rand::distributions::Uniform<usize>,
+    /// how many blocks of `edges_idx` are unknown, zero once the slot is free
+    cnt_unknown: usize,
 }
 
 #[derive(Debug)]
@@ -24,6 +64,85 @@ pub struct Statistics {
     pub cnt_chunks: usize,
     pub overhead: f32,
     pub unknown_chunks: usize,
+    /// chunks recovered by peeling, including the ones received directly
+    pub cnt_peeled: usize,
+    /// chunks recovered by Gaussian elimination
+    pub cnt_eliminated: usize,
+    /// packets rejected because their checksum did not match
+    pub cnt_corrupt: usize,
+    /// packets rejected because their tag was missing or did not match the key
+    pub cnt_unauthenticated: usize,
+}
+
+/// Errors returned when a droplet can not be caught. Apart from `Io`, the
+/// droplet is rejected before it touches the decoder, which stays usable.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The block index of an edges droplet lies outside of the object
+    BlockOutOfRange { index: usize, cnt_blocks: usize },
+    /// The payload size differs from the block size
+    PayloadLength { expected: usize, actual: usize },
+    /// A seeded droplet has degree zero
+    InvalidDegree,
+    /// The droplet belongs to another object, or to the other kind of code
+    ParameterMismatch,
+    /// The encoding symbol id of a RaptorQ symbol is too large for its object
+    EsiOutOfRange { esi: u32 },
+    /// All blocks are recovered already
+    AlreadyFinished,
+    /// The packet could not be parsed
+    Wire(WireError),
+    /// The store failed, the state of the decoder is undefined
+    Io(io::Error),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::BlockOutOfRange { index, cnt_blocks } => write!(
+                f,
+                "block index {} out of range for {} blocks",
+                index, cnt_blocks
+            ),
+            DecodeError::PayloadLength { expected, actual } => write!(
+                f,
+                "payload has {} bytes, block size is {}",
+                actual, expected
+            ),
+            DecodeError::InvalidDegree => write!(f, "seeded droplet has degree zero"),
+            DecodeError::ParameterMismatch => {
+                write!(f, "droplet does not belong to the decoded object")
+            }
+            DecodeError::EsiOutOfRange { esi } => {
+                write!(f, "encoding symbol id {} out of range", esi)
+            }
+            DecodeError::AlreadyFinished => write!(f, "object is decoded already"),
+            DecodeError::Wire(err) => write!(f, "invalid packet: {}", err),
+            DecodeError::Io(err) => write!(f, "store failed: {}", err),
+        }
+    }
+}
+
+impl error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            DecodeError::Wire(err) => Some(err),
+            DecodeError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(err: io::Error) -> DecodeError {
+        DecodeError::Io(err)
+    }
+}
+
+impl From<WireError> for DecodeError {
+    fn from(err: WireError) -> DecodeError {
+        DecodeError::Wire(err)
+    }
 }
 
 impl Decoder {
@@ -53,7 +172,7 @@ impl Decoder {
     ///
     ///     loop {
     ///         let drop = enc.next();
-    ///         match dec.catch(drop) {
+    ///         match dec.catch(drop).unwrap() {
     ///             CatchResult::Missing(stats) => {
     ///                 println!("Missing blocks {:?}", stats);
     ///             }
@@ -65,15 +184,146 @@ impl Decoder {
     ///                 //write data to disk??
     ///                 return
     ///             }
+    ///             CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
     ///         }
     ///     }
     /// }
     /// ```
     pub fn new(len: usize, blocksize: usize) -> Decoder {
-        let number_of_chunks = ((len as f32) / blocksize as f32).ceil() as usize;
-        let data: Vec<u8> = vec![0; number_of_chunks * blocksize];
-        let mut edges: Vec<Block> = Vec::with_capacity(number_of_chunks);
-        for i in 0..number_of_chunks {
+        Decoder::with_mode(len, blocksize, DecoderMode::Peeling)
+    }
+
+    /// Creates a new Decoder for LT codes which decodes with the given mode
+    pub fn with_mode(len: usize, blocksize: usize, mode: DecoderMode) -> Decoder {
+        Decoder::with_aux_blocks(len, blocksize, 0, mode)
+    }
+
+    /// Creates a new Decoder for the object a descriptor announces, e.g. the one
+    /// of the first packet received. Fails if the object does not fit into memory
+    /// addresses on this platform, or exceeds `limits`: the descriptor is not
+    /// trusted, and the whole object is allocated up front.
+    pub fn from_descriptor(
+        descriptor: &ObjectDescriptor,
+        mode: DecoderMode,
+        limits: Limits,
+    ) -> Result<Decoder, WireError> {
+        let params = descriptor.params()?;
+        limits.check(&params)?;
+        Ok(Decoder::with_mode(params.len, params.blocksize, mode))
+    }
+
+    /// Creates a Decoder whose droplets may also cover `cnt_aux` auxiliary blocks
+    /// appended after the message. Only the message blocks count as chunks.
+    pub(crate) fn with_aux_blocks(
+        len: usize,
+        blocksize: usize,
+        cnt_aux: usize,
+        mode: DecoderMode,
+    ) -> Decoder {
+        Decoder::build(len, blocksize, cnt_aux, mode, vec![0; len])
+    }
+
+    /// Catches a Droplet
+    /// When it is possible to reconstruct a set, the bytes are returned.
+    /// Invalid droplets are rejected with an error, see `try_catch`.
+    ///
+    /// The data is moved out of the decoder without a copy, `into_inner`
+    /// returns an empty object afterwards.
+    pub fn catch(&mut self, drop: Droplet) -> Result<CatchResult, DecodeError> {
+        let stats = self.try_catch(drop)?;
+
+        Ok(if self.digest_mismatch {
+            CatchResult::DigestMismatch(stats)
+        } else if self.unknown_chunks == 0 {
+            CatchResult::Finished(std::mem::take(&mut self.store), stats)
+        } else {
+            CatchResult::Missing(stats)
+        })
+    }
+
+    /// Parses a packet and catches its droplet.
+    ///
+    /// Packets whose checksum does not match are rejected and counted in
+    /// `Statistics::cnt_corrupt`, so they never reach the recovered blocks.
+    /// With a key set by `set_auth_key`, packets without a valid tag are
+    /// rejected as well and counted in `Statistics::cnt_unauthenticated`.
+    pub fn catch_packet(&mut self, packet: &[u8]) -> Result<CatchResult, DecodeError> {
+        let parsed = match self.auth_key {
+            Some(ref key) => Droplet::from_bytes_authenticated(packet, key),
+            None => Droplet::from_bytes(packet),
+        };
+        let (drop, params) = match parsed {
+            Ok(parsed) => parsed,
+            Err(WireError::ChecksumMismatch) => {
+                self.cnt_corrupt += 1;
+                return Err(WireError::ChecksumMismatch.into());
+            }
+            Err(WireError::Unauthenticated) => {
+                self.cnt_unauthenticated += 1;
+                return Err(WireError::Unauthenticated.into());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if params.len != self.total_length || params.blocksize != self.blocksize {
+            return Err(DecodeError::ParameterMismatch);
+        }
+        self.catch(drop)
+    }
+}
+
+impl<S: BlockStore> Decoder<S> {
+    /// Creates a new Decoder for LT codes which writes the recovered blocks to `store`.
+    ///
+    /// Only the droplets which are not resolved yet are kept in memory, the
+    /// blocks are written as soon as they are known.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate fountaincode;
+    ///
+    /// fn main() {
+    ///     use self::fountaincode::robust_encoder::RobustEncoder;
+    ///     use self::fountaincode::decoder::Decoder;
+    ///     use self::fountaincode::encoder::Encoder;
+    ///     use self::fountaincode::storage::SeekStore;
+    ///     use self::fountaincode::types::*;
+    ///     use self::rand::{thread_rng, Rng};
+    ///     use rand::distributions::Alphanumeric;
+    ///     use std::io::Cursor;
+    ///
+    ///     let s:String = thread_rng().sample_iter(Alphanumeric).take(1024).collect();
+    ///     let buf = s.into_bytes();
+    ///     let to_compare = buf.clone();
+    ///     let length = buf.len();
+    ///
+    ///     let mut enc = RobustEncoder::new(buf, 64, EncoderType::Random, 0.2, None, 0.05);
+    ///     let store = SeekStore::new(Cursor::new(Vec::new()));
+    ///     let mut dec = Decoder::with_store(length, 64, DecoderMode::Peeling, store);
+    ///
+    ///     while !dec.is_finished() {
+    ///         dec.try_catch(enc.next()).unwrap();
+    ///     }
+    ///     let file = dec.into_inner().into_inner();
+    ///     assert_eq!(to_compare, file.into_inner());
+    /// }
+    /// ```
+    pub fn with_store(len: usize, blocksize: usize, mode: DecoderMode, store: S) -> Decoder<S> {
+        Decoder::build(len, blocksize, 0, mode, store)
+    }
+
+    fn build(
+        len: usize,
+        blocksize: usize,
+        cnt_aux: usize,
+        mode: DecoderMode,
+        store: S,
+    ) -> Decoder<S> {
+        let number_of_chunks = cnt_blocks(len, blocksize);
+        let cnt_blocks = number_of_chunks + cnt_aux;
+        let mut edges: Vec<Block> = Vec::with_capacity(cnt_blocks);
+        for i in 0..cnt_blocks {
             let blk = Block::new(i, Vec::new(), blocksize * i, false);
             edges.push(blk);
         }
@@ -84,107 +334,547 @@ impl Decoder {
             unknown_chunks: number_of_chunks,
             cnt_received_drops: 0,
             blocks: edges,
-            data,
+            store,
+            aux: vec![0; cnt_aux * blocksize],
             blocksize,
-            dist: Uniform::new(0, number_of_chunks),
+            mode,
+            droplets: Vec::new(),
+            free: Vec::new(),
+            ripple: Vec::new(),
+            scratch: Vec::new(),
+            parallel: false,
+            deferred: Vec::new(),
+            next_elimination: number_of_chunks,
+            cnt_peeled: 0,
+            cnt_eliminated: 0,
+            cnt_corrupt: 0,
+            cnt_unauthenticated: 0,
+            auth_key: None,
+            digest: None,
+            digest_mismatch: false,
+        }
+    }
+
+    /// Sets the digest of the object. The recovered object is checked against
+    /// it, and a mismatch is reported instead of the data.
+    pub fn set_digest(&mut self, digest: Digest) {
+        self.digest = Some(digest);
+    }
+
+    /// Only accepts packets authenticated with `key` in `catch_packet`.
+    /// Droplets passed to `catch` directly are not checked.
+    pub fn set_auth_key(&mut self, key: AuthKey) {
+        self.auth_key = Some(key);
+    }
+
+    /// Defers the XOR of the payloads until the object is complete, or the
+    /// elimination needs them, and then computes them on the rayon thread pool.
+    /// The peeling stays sequential and recovers the same blocks. Until the
+    /// object is complete the store does not hold the recovered blocks.
+    /// It can be changed at any time, turning it off computes the deferred
+    /// payloads, which fails if the store does.
+    #[cfg(feature = "parallel")]
+    pub fn set_parallel(&mut self, parallel: bool) -> io::Result<()> {
+        if !parallel {
+            self.flush()?;
         }
+        self.parallel = parallel;
+        Ok(())
     }
 
-    fn process_droplet(&mut self, droplet: RxDroplet) {
-        let mut drops: Vec<RxDroplet> = Vec::new();
-        drops.push(droplet);
-        while let Some(drop) = drops.pop() {
-            let edges = drop.edges_idx.clone();
-            // TODO: Maybe add shortcut for the first wave of
-            // systematic codes, reduce overhead
+    /// The object is recovered, but does not match the digest set by `set_digest`.
+    /// Some droplet was corrupt, the data must not be used.
+    pub fn is_corrupt(&self) -> bool {
+        self.digest_mismatch
+    }
+
+    /// Adds a known relation between blocks, e.g. of an outer code.
+    /// It is processed like a droplet, but not counted as one.
+    pub(crate) fn add_equation(&mut self, edges_idx: Vec<usize>, data: Vec<u8>) -> io::Result<()> {
+        self.process_droplet(RxDroplet { edges_idx, data })
+    }
+
+    /// Reads a known block, the bytes after the end of the object are zero
+    fn read_block(&mut self, idx: usize, buf: &mut [u8]) -> io::Result<()> {
+        let begin = self.blocks[idx].begin_at;
+        if idx >= self.number_of_chunks {
+            let begin = begin - self.number_of_chunks * self.blocksize;
+            buf.copy_from_slice(&self.aux[begin..begin + self.blocksize]);
+            return Ok(());
+        }
+        let (data, padding) = buf.split_at_mut(block_len(self.total_length, self.blocksize, idx));
+        self.store.read_at(begin as u64, data)?;
+        for b in padding.iter_mut() {
+            *b = 0;
+        }
+        Ok(())
+    }
+
+    /// Writes a known block, the padding of the last block is not written
+    fn write_block(&mut self, idx: usize, data: &[u8]) -> io::Result<()> {
+        let begin = self.blocks[idx].begin_at;
+        if idx >= self.number_of_chunks {
+            let begin = begin - self.number_of_chunks * self.blocksize;
+            self.aux[begin..begin + self.blocksize].copy_from_slice(data);
+        } else {
+            let end = block_len(self.total_length, self.blocksize, idx);
+            self.store.write_at(begin as u64, &data[..end])?;
+        }
+        Ok(())
+    }
+
+    fn set_known(&mut self, idx: usize) {
+        self.blocks[idx].is_known = true;
+        if idx < self.number_of_chunks {
+            self.unknown_chunks -= 1;
+        }
+    }
 
-            for ed in edges {
-                // the list is edited, hence we copy first
-                let block = self.blocks.get_mut(ed).unwrap();
-                if block.is_known {
-                    let mut b_drop = drop.clone();
-                    for i in 0..self.blocksize {
-                        b_drop.data[i] ^= self.data[block.begin_at + i];
+    /// Writes a block recovered by peeling and XORs it out of the droplets
+    /// which wait for it. Droplets left with a single unknown block join the ripple.
+    fn peel(&mut self, idx: usize, data: &[u8]) -> io::Result<()> {
+        self.write_block(idx, data)?;
+        self.set_known(idx);
+        if idx < self.number_of_chunks {
+            self.cnt_peeled += 1;
+        }
+        for slot in std::mem::take(&mut self.blocks[idx].edges) {
+            let drop = &mut self.droplets[slot];
+            if drop.cnt_unknown == 0 {
+                // the droplet which recovered the block
+                continue;
+            }
+            xor_into(&mut drop.data, data);
+            drop.cnt_unknown -= 1;
+            match drop.cnt_unknown {
+                0 => self.release(slot),
+                1 => self.ripple.push(slot),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Like `peel`, but only remembers that the droplet in `slot` recovers
+    /// the block. Its payload is computed by `flush`.
+    fn peel_deferred(&mut self, idx: usize, slot: usize) {
+        self.set_known(idx);
+        if idx < self.number_of_chunks {
+            self.cnt_peeled += 1;
+        }
+        self.blocks[idx].recovered_by = Some(slot);
+        self.deferred.push(idx);
+        for waiting in std::mem::take(&mut self.blocks[idx].edges) {
+            let drop = &mut self.droplets[waiting];
+            if drop.cnt_unknown == 0 {
+                continue;
+            }
+            drop.cnt_unknown -= 1;
+            match drop.cnt_unknown {
+                0 => self.release(waiting),
+                1 => self.ripple.push(waiting),
+                _ => {}
+            }
+        }
+    }
+
+    /// Frees the memory of a droplet, its slot is reused by the next one stored
+    fn release(&mut self, slot: usize) {
+        self.droplets[slot] = Slot {
+            edges_idx: Vec::new(),
+            data: Vec::new(),
+            cnt_unknown: 0,
+        };
+        self.free.push(slot);
+    }
+
+    /// Forgets every droplet, e.g. once all blocks are known
+    fn clear_droplets(&mut self) {
+        self.droplets.clear();
+        self.free.clear();
+        self.ripple.clear();
+        for block in self.blocks.iter_mut() {
+            block.edges.clear();
+        }
+    }
+
+    /// XORs the blocks in the store out of the droplet. It is stored if a
+    /// block stays unknown, and recovers it if it is the only one.
+    fn process_droplet(&mut self, droplet: RxDroplet) -> io::Result<()> {
+        let RxDroplet {
+            mut edges_idx,
+            mut data,
+        } = droplet;
+        let mut scratch = std::mem::take(&mut self.scratch);
+        scratch.resize(self.blocksize, 0);
+        let mut pos = 0;
+        while pos < edges_idx.len() {
+            let block = &self.blocks[edges_idx[pos]];
+            if block.is_known && block.recovered_by.is_none() {
+                self.read_block(edges_idx[pos], &mut scratch)?;
+                xor_into(&mut data, &scratch);
+                edges_idx.swap_remove(pos);
+            } else {
+                pos += 1;
+            }
+        }
+        self.scratch = scratch;
+
+        // without deferred blocks a droplet of one block recovers it directly
+        if !self.parallel {
+            match edges_idx[..] {
+                [] => return Ok(()),
+                [idx] => {
+                    self.peel(idx, &data)?;
+                    return self.peel_ripple();
+                }
+                _ => {}
+            }
+        }
+
+        // the known blocks left are deferred
+        let cnt_unknown = edges_idx
+            .iter()
+            .filter(|idx| !self.blocks[**idx].is_known)
+            .count();
+        if cnt_unknown == 0 {
+            return Ok(());
+        }
+        let slot = self.free.pop().unwrap_or(self.droplets.len());
+        for idx in edges_idx.iter() {
+            if !self.blocks[*idx].is_known {
+                self.blocks[*idx].edges.push(slot);
+            }
+        }
+        let drop = Slot {
+            edges_idx,
+            data,
+            cnt_unknown,
+        };
+        if slot == self.droplets.len() {
+            self.droplets.push(drop);
+        } else {
+            self.droplets[slot] = drop;
+        }
+        if cnt_unknown == 1 {
+            self.ripple.push(slot);
+        }
+        self.peel_ripple()
+    }
+
+    /// Recovers the blocks of the droplets in the ripple until it is empty
+    fn peel_ripple(&mut self) -> io::Result<()> {
+        while let Some(slot) = self.ripple.pop() {
+            // the last block may have been recovered by another droplet meanwhile
+            if self.droplets[slot].cnt_unknown != 1 {
+                continue;
+            }
+            let blocks = &self.blocks;
+            let idx = self.droplets[slot]
+                .edges_idx
+                .iter()
+                .copied()
+                .find(|idx| !blocks[*idx].is_known)
+                .expect("one block is unknown");
+            self.droplets[slot].cnt_unknown = 0;
+            if self.parallel {
+                self.peel_deferred(idx, slot);
+            } else {
+                let data = std::mem::take(&mut self.droplets[slot].data);
+                self.release(slot);
+                self.peel(idx, &data)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Computes the payloads of the blocks recovered by `peel_deferred`, writes
+    /// them to the store and XORs them out of the droplets which still wait.
+    ///
+    /// A payload is the droplet XOR the other deferred blocks of the droplet,
+    /// which were recovered before. The blocks of one level of this
+    /// dependency graph are computed in parallel.
+    fn flush(&mut self) -> io::Result<()> {
+        let deferred = std::mem::take(&mut self.deferred);
+        if deferred.is_empty() {
+            return Ok(());
+        }
+        let mut level = vec![0; self.blocks.len()];
+        let mut levels: Vec<Vec<usize>> = Vec::new();
+        for idx in deferred.iter() {
+            let slot = self.blocks[*idx]
+                .recovered_by
+                .expect("the block is deferred");
+            let l = self.droplets[slot]
+                .edges_idx
+                .iter()
+                .filter(|e| *e != idx && self.blocks[**e].recovered_by.is_some())
+                .map(|e| level[*e] + 1)
+                .max()
+                .unwrap_or(0);
+            level[*idx] = l;
+            if levels.len() <= l {
+                levels.resize_with(l + 1, Vec::new);
+            }
+            levels[l].push(*idx);
+        }
+
+        for blocks_of_level in levels {
+            let mut payloads: Vec<(usize, Vec<u8>)> = blocks_of_level
+                .into_iter()
+                .map(|idx| {
+                    let slot = self.blocks[idx]
+                        .recovered_by
+                        .expect("the block is deferred");
+                    (idx, std::mem::take(&mut self.droplets[slot].data))
+                })
+                .collect();
+            let (blocks, droplets) = (&self.blocks, &self.droplets);
+            for_each_mut(&mut payloads, |(idx, data)| {
+                let slot = blocks[*idx].recovered_by.expect("the block is deferred");
+                for e in droplets[slot].edges_idx.iter() {
+                    match blocks[*e].recovered_by {
+                        Some(dep) if e != idx => xor_into(data, &droplets[dep].data),
+                        _ => {}
                     }
-                    let pos = b_drop.edges_idx.iter().position(|x| x == &ed).unwrap();
-                    b_drop.edges_idx.remove(pos);
-                } else {
-                    block.edges.push(drop.clone());
                 }
+            });
+            for (idx, data) in payloads {
+                let slot = self.blocks[idx]
+                    .recovered_by
+                    .expect("the block is deferred");
+                self.droplets[slot].data = data;
+            }
+        }
+
+        // the payloads leave the droplets, `level` maps a block to its payload from now on
+        let payloads: Vec<Vec<u8>> = deferred
+            .iter()
+            .enumerate()
+            .map(|(pos, idx)| {
+                level[*idx] = pos;
+                let slot = self.blocks[*idx]
+                    .recovered_by
+                    .expect("the block is deferred");
+                std::mem::take(&mut self.droplets[slot].data)
+            })
+            .collect();
+        let blocks = &self.blocks;
+        for_each_mut(&mut self.droplets, |drop| {
+            if drop.cnt_unknown == 0 {
+                return;
             }
-            if drop.clone().edges_idx.len() == 1 {
-                let first_idx = *drop.edges_idx.clone().get(0).unwrap();
+            let Slot {
+                edges_idx, data, ..
+            } = drop;
+            edges_idx.retain(|e| match blocks[*e].recovered_by {
+                Some(_) => {
+                    xor_into(data, &payloads[level[*e]]);
+                    false
+                }
+                None => true,
+            });
+        });
+
+        for (idx, data) in deferred.iter().zip(payloads.iter()) {
+            self.write_block(*idx, data)?;
+        }
+        for idx in deferred {
+            let slot = self.blocks[idx]
+                .recovered_by
+                .take()
+                .expect("the block is deferred");
+            self.release(slot);
+        }
+        Ok(())
+    }
 
-                let block = self.blocks.get_mut(first_idx).unwrap();
+    /// Solves the droplets peeling could not resolve by Gaussian elimination over GF(2).
+    /// The rank is determined on the edges first, the payloads are only touched
+    /// once all unknown blocks can be recovered.
+    fn eliminate(&mut self) -> io::Result<()> {
+        // after the peeling every stored droplet waits for two blocks or more
+        let live: Vec<usize> = (0..self.droplets.len())
+            .filter(|slot| self.droplets[*slot].cnt_unknown > 1)
+            .collect();
+        let unknown: Vec<usize> = (0..self.blocks.len())
+            .filter(|idx| !self.blocks[*idx].is_known)
+            .collect();
+        if live.len() < unknown.len() {
+            self.next_elimination = self.cnt_received_drops + unknown.len() - live.len();
+            return Ok(());
+        }
 
-                if !block.is_known {
-                    {
-                        let b_drop = &drop;
-                        for i in 0..self.blocksize {
-                            self.data[block.begin_at + i] = b_drop.data[i];
-                        }
+        let mut column = vec![0; self.blocks.len()];
+        for (col, idx) in unknown.iter().enumerate() {
+            column[*idx] = col;
+        }
+        let words = unknown.len().div_ceil(64);
+        let mut rows: Vec<Vec<u64>> = live
+            .iter()
+            .map(|slot| {
+                let mut row = vec![0u64; words];
+                for idx in self.droplets[*slot].edges_idx.iter() {
+                    if !self.blocks[*idx].is_known {
+                        row[column[*idx] / 64] ^= 1 << (column[*idx] % 64);
                     }
-                    block.is_known = true;
-                    self.unknown_chunks -= 1;
-
-                    while let Some(mut edge) = block.edges.pop() {
-                        let m_edge = &mut edge;
-
-                        if m_edge.edges_idx.len() == 1 {
-                            drops.push(edge);
-                        } else {
-                            for i in 0..self.blocksize {
-                                m_edge.data[i] ^= self.data[block.begin_at + i]
-                            }
-
-                            let pos = m_edge
-                                .edges_idx
-                                .iter()
-                                .position(|x| x == &block.idx)
-                                .unwrap();
-                            m_edge.edges_idx.remove(pos);
-                            if m_edge.edges_idx.len() == 1 {
-                                drops.push(edge.clone());
-                            }
-                        }
+                }
+                row
+            })
+            .collect();
+
+        // Gauss-Jordan, remember the row operations to replay them on the payloads
+        let mut pivots = Vec::with_capacity(unknown.len());
+        let mut used = vec![false; rows.len()];
+        let mut ops = Vec::new();
+        for col in 0..unknown.len() {
+            let (word, bit) = (col / 64, 1 << (col % 64));
+            let pivot = match (0..rows.len()).find(|r| !used[*r] && rows[*r][word] & bit != 0) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+            used[pivot] = true;
+            let pivot_row = rows[pivot].clone();
+            for (r, row) in rows.iter_mut().enumerate() {
+                if r != pivot && row[word] & bit != 0 {
+                    for (a, b) in row.iter_mut().zip(pivot_row.iter()) {
+                        *a ^= b;
                     }
+                    ops.push((r, pivot));
                 }
             }
+            pivots.push(pivot);
+        }
+        if pivots.len() < unknown.len() {
+            // every further droplet raises the rank by one at best
+            self.next_elimination = self.cnt_received_drops + unknown.len() - pivots.len();
+            return Ok(());
         }
+
+        for (target, source) in ops {
+            let (target, source) = pair_mut(&mut self.droplets, live[target], live[source]);
+            xor_into(&mut target.data, &source.data);
+        }
+        for (idx, pivot) in unknown.into_iter().zip(pivots) {
+            let data = std::mem::take(&mut self.droplets[live[pivot]].data);
+            self.write_block(idx, &data)?;
+            self.set_known(idx);
+            if idx < self.number_of_chunks {
+                self.cnt_eliminated += 1;
+            }
+        }
+        // every block is known
+        self.clear_droplets();
+        Ok(())
     }
 
-    /// Catches a Droplet
-    /// When it is possible to reconstruct a set, the bytes are returned
-    pub fn catch(&mut self, drop: Droplet) -> CatchResult {
-        self.cnt_received_drops += 1;
+    /// Catches a Droplet and writes the blocks it recovers to the store.
+    ///
+    /// Invalid droplets are rejected and not counted. After an `Io` error the
+    /// state of the decoder is undefined.
+    pub fn try_catch(&mut self, drop: Droplet) -> Result<Statistics, DecodeError> {
+        if self.is_finished() {
+            return Err(DecodeError::AlreadyFinished);
+        }
+        if drop.data.len() != self.blocksize {
+            return Err(DecodeError::PayloadLength {
+                expected: self.blocksize,
+                actual: drop.data.len(),
+            });
+        }
         let sample: Vec<usize> = match drop.droptype {
+            DropType::Seeded(_, 0) => return Err(DecodeError::InvalidDegree),
             DropType::Seeded(seed, degree) => {
-                get_sample_from_rng_by_seed(seed, self.dist, degree).collect()
+                get_sample_from_rng_by_seed(seed, self.blocks.len(), degree).collect()
             }
-            DropType::Edges(edges) => vec![edges],
+            DropType::Edges(index) if index >= self.blocks.len() => {
+                return Err(DecodeError::BlockOutOfRange {
+                    index,
+                    cnt_blocks: self.blocks.len(),
+                })
+            }
+            DropType::Edges(index) => vec![index],
+            DropType::Esi(_) => return Err(DecodeError::ParameterMismatch),
         };
+        self.cnt_received_drops += 1;
 
         let rxdrop = RxDroplet {
             edges_idx: sample,
             data: drop.data,
         };
-        self.process_droplet(rxdrop);
-        let stats = Statistics {
+        self.process_droplet(rxdrop)?;
+        if self.mode == DecoderMode::Elimination
+            && self.unknown_chunks > 0
+            && self.cnt_received_drops >= self.next_elimination
+        {
+            self.flush()?;
+            self.eliminate()?;
+        }
+        if self.is_finished() {
+            self.flush()?;
+            self.clear_droplets();
+            self.store.flush()?;
+            if let Some(expected) = self.digest {
+                self.digest_mismatch = self.compute_digest()? != expected;
+            }
+        }
+        Ok(self.statistics())
+    }
+
+    /// Reads the recovered object back from the store
+    fn compute_digest(&mut self) -> io::Result<Digest> {
+        let mut hasher = Hasher::default();
+        let mut buf = vec![0; self.blocksize];
+        for idx in 0..self.number_of_chunks {
+            let end = block_len(self.total_length, self.blocksize, idx);
+            self.read_block(idx, &mut buf)?;
+            hasher.update(&buf[..end]);
+        }
+        Ok(hasher.finish())
+    }
+
+    pub fn statistics(&self) -> Statistics {
+        Statistics {
             cnt_droplets: self.cnt_received_drops,
             cnt_chunks: self.number_of_chunks,
             overhead: self.cnt_received_drops as f32 * 100.0 / self.number_of_chunks as f32,
             unknown_chunks: self.unknown_chunks,
-        };
-
-        if self.unknown_chunks == 0 {
-            let mut result = Vec::with_capacity(self.total_length);
-            for i in 0..self.total_length {
-                // TODO: we should be able to do that without copying
-                result.push(self.data[i]);
-            }
-            CatchResult::Finished(result, stats)
-        } else {
-            CatchResult::Missing(stats)
+            cnt_peeled: self.cnt_peeled,
+            cnt_eliminated: self.cnt_eliminated,
+            cnt_corrupt: self.cnt_corrupt,
+            cnt_unauthenticated: self.cnt_unauthenticated,
         }
     }
+
+    /// All blocks of the object are recovered
+    pub fn is_finished(&self) -> bool {
+        self.unknown_chunks == 0
+    }
+
+    /// Returns the store, it holds the object once the decoder is finished
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+}
+
+/// Borrows `items[a]` mutably and `items[b]`, which must differ
+fn pair_mut<T>(items: &mut [T], a: usize, b: usize) -> (&mut T, &T) {
+    if a < b {
+        let (head, tail) = items.split_at_mut(b);
+        (&mut head[a], &tail[0])
+    } else {
+        let (head, tail) = items.split_at_mut(a);
+        (&mut tail[0], &head[b])
+    }
+}
+
+/// Runs `f` on every item, on the rayon thread pool with the `parallel` feature
+fn for_each_mut<T: Send, F: Fn(&mut T) + Send + Sync>(items: &mut [T], f: F) {
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        items.par_iter_mut().for_each(f);
+    }
+    #[cfg(not(feature = "parallel"))]
+    items.iter_mut().for_each(f);
 }
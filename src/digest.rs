@@ -0,0 +1,28 @@
+//! Whole-object digests.
+//!
+//! A decoder which knows the digest of its object verifies the recovered
+//! data before it reports the object as finished, see `Decoder::set_digest`.
+
+use sha2::{Digest as _, Sha256};
+
+/// SHA-256 of an object
+pub type Digest = [u8; 32];
+
+/// Computes the digest of a whole object
+pub fn digest(data: &[u8]) -> Digest {
+    Sha256::digest(data).into()
+}
+
+/// Computes a digest over data which arrives in pieces
+#[derive(Default)]
+pub(crate) struct Hasher(Sha256);
+
+impl Hasher {
+    pub fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    pub fn finish(self) -> Digest {
+        self.0.finalize().into()
+    }
+}
@@ -1,7 +1,12 @@
-use crate::types::DropType;
+use crate::{
+    auth::AuthKey,
+    types::DropType,
+    wire::{self, Header, ObjectParams, Trailer, WireError},
+};
+use std::convert::TryFrom;
 
 /// A Droplet is created by the Encoder.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Droplet {
     /// The droptype can be based on seed or a list of edges
     pub droptype: DropType,
@@ -13,6 +18,83 @@ impl Droplet {
     pub fn new(droptype: DropType, data: Vec<u8>) -> Droplet {
         Droplet { droptype, data }
     }
+
+    /// Serializes the droplet into a packet, see `wire` for the layout.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the block size or the degree do not fit into 32 bits.
+    pub fn to_bytes(&self, params: &ObjectParams) -> Vec<u8> {
+        self.serialize(params, Trailer::None, None)
+    }
+
+    /// Like `to_bytes`, but the packet carries a CRC32C so corruption is detected
+    pub fn to_bytes_with_checksum(&self, params: &ObjectParams) -> Vec<u8> {
+        self.serialize(params, Trailer::Checksum, None)
+    }
+
+    /// Like `to_bytes`, but the packet carries an HMAC tag so forged droplets are detected
+    pub fn to_bytes_authenticated(&self, params: &ObjectParams, key: &AuthKey) -> Vec<u8> {
+        self.serialize(params, Trailer::Tag, Some(key))
+    }
+
+    fn serialize(&self, params: &ObjectParams, trailer: Trailer, key: Option<&AuthKey>) -> Vec<u8> {
+        let (kind, id, degree) = match self.droptype {
+            DropType::Seeded(seed, degree) => (
+                wire::KIND_SEEDED,
+                seed,
+                u32::try_from(degree).expect("degree does not fit into u32"),
+            ),
+            DropType::Edges(idx) => (wire::KIND_EDGES, idx as u64, 0),
+            DropType::Esi(esi) => (wire::KIND_ESI, u64::from(esi), 0),
+        };
+        let header = Header {
+            kind,
+            id,
+            degree,
+            params: *params,
+            payload_len: self.data.len(),
+            trailer,
+        };
+
+        let mut buf = Vec::with_capacity(header.payload_offset() + self.data.len());
+        wire::write_header(&mut buf, &header);
+        buf.extend_from_slice(&self.data);
+        wire::write_trailer(&mut buf, &header, key);
+        buf
+    }
+
+    /// Parses a packet created by any of the `to_bytes` methods.
+    /// The tag of an authenticated packet is not checked.
+    ///
+    /// Returns the droplet together with the parameters of the object it belongs to.
+    pub fn from_bytes(buf: &[u8]) -> Result<(Droplet, ObjectParams), WireError> {
+        let header = wire::read_header(buf)?;
+        Ok(Droplet::from_header(buf, header))
+    }
+
+    /// Parses a packet created by `to_bytes_authenticated` with the same key.
+    /// Packets without a valid tag are rejected with `WireError::Unauthenticated`.
+    pub fn from_bytes_authenticated(
+        buf: &[u8],
+        key: &AuthKey,
+    ) -> Result<(Droplet, ObjectParams), WireError> {
+        let header = wire::read_header(buf)?;
+        wire::verify_tag(buf, &header, key)?;
+        Ok(Droplet::from_header(buf, header))
+    }
+
+    fn from_header(buf: &[u8], header: Header) -> (Droplet, ObjectParams) {
+        let droptype = match header.kind {
+            wire::KIND_SEEDED => DropType::Seeded(header.id, header.degree as usize),
+            // the esi was checked to fit into 32 bits
+            wire::KIND_ESI => DropType::Esi(header.id as u32),
+            // the index was checked against the block count, which is a usize
+            _ => DropType::Edges(header.id as usize),
+        };
+        let data = buf[header.payload_offset()..].to_vec();
+        (Droplet::new(droptype, data), header.params)
+    }
 }
 
 #[derive(Debug, Clone)]
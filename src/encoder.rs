@@ -1,5 +1,20 @@
 use crate::droplet::Droplet;
+use crate::prng::{splitmix64, Prng};
+use std::io;
 
 pub trait Encoder {
     fn next(&mut self) -> Droplet;
+
+    /// Like `next`, but returns the errors of encoders reading from a `BlockSource`
+    fn try_next(&mut self) -> io::Result<Droplet> {
+        Ok(self.next())
+    }
+}
+
+/// The random number generator of droplet `idx` in the stream of `seed`.
+/// It is seeded with the idx-th output of splitmix64, so any droplet can be
+/// produced without the ones before it.
+pub(crate) fn droplet_rng(seed: u64, idx: usize) -> Prng {
+    let mut state = seed.wrapping_add((idx as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    Prng::new(splitmix64(&mut state))
 }
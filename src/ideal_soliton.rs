@@ -20,6 +20,11 @@ impl IdealSoliton {
 impl Soliton for IdealSoliton {
     fn next(&mut self) -> usize {
         let y = self.rng.gen::<f32>();
+        self.degree(f64::from(y))
+    }
+
+    fn degree(&self, u: f64) -> usize {
+        let y = u as f32;
         if y >= self.limit {
             (1.0 / y).ceil() as usize
         } else {
@@ -1,12 +1,31 @@
 extern crate rand;
 
+pub mod auth;
 pub mod block;
+pub mod custom_distribution;
 pub mod decoder;
+pub mod digest;
 pub mod droplet;
 pub mod encoder;
 pub mod ideal_encoder;
 pub mod ideal_soliton;
+pub mod lt_encoder;
+#[cfg(feature = "net")]
+pub mod net;
+pub mod online_decoder;
+pub mod online_encoder;
+pub mod online_soliton;
+pub mod optimizer;
+pub mod prng;
+pub mod raptorq;
+pub mod raptorq_decoder;
+pub mod raptorq_encoder;
+mod raptorq_tables;
 pub mod robust_encoder;
 pub mod robust_soliton;
+pub mod session;
 pub mod soliton;
+pub mod storage;
 pub mod types;
+pub mod wire;
+pub mod xor;
@@ -0,0 +1,291 @@
+use crate::{
+    droplet::Droplet,
+    encoder::{droplet_rng, Encoder},
+    ideal_soliton::IdealSoliton,
+    prng::Prng,
+    soliton::Soliton,
+    storage::{read_block, BlockSource, SeekSource},
+    types::{DropType, EncoderType},
+    wire::cnt_blocks,
+    xor::xor_into,
+};
+use std::io::{self, Read, Seek};
+use std::ops::Range;
+
+/// Encoder for Luby transform codes which draws the degrees from the distribution `S`.
+/// `IdealEncoder` and `RobustEncoder` are aliases with the soliton distributions.
+#[derive(Clone)]
+pub struct LtEncoder<S, B = Vec<u8>> {
+    source: B,
+    len: usize,
+    blocksize: usize,
+    seed: u64,
+    sol: S,
+    /// the index of the next droplet
+    pub cnt: usize,
+    cnt_systematic: usize,
+}
+
+/// The distribution of a builder which was not given one
+pub type DefaultDistribution = fn(usize, u64) -> IdealSoliton;
+
+/// Collects the options of an `LtEncoder`. The distribution is created by a
+/// function of the number of blocks and the seed once the object is known.
+///
+/// # Examples
+///
+/// ```
+/// extern crate fountaincode;
+///
+/// fn main() {
+///     use fountaincode::encoder::Encoder;
+///     use fountaincode::lt_encoder::LtEncoder;
+///     use fountaincode::robust_soliton::RobustSoliton;
+///     use fountaincode::types::EncoderType;
+///
+///     let mut enc = LtEncoder::builder(64)
+///         .encodertype(EncoderType::Systematic)
+///         .seed(7)
+///         .distribution(|k, seed| RobustSoliton::new(k, seed, 0.1, None, 0.05))
+///         .build(vec![1; 1024]);
+///
+///     for i in 1..10 {
+///         println!("droplet {:?}: {:?}", i, enc.next());
+///     }
+/// }
+/// ```
+#[derive(Clone)]
+pub struct LtEncoderBuilder<F = DefaultDistribution> {
+    blocksize: usize,
+    encodertype: EncoderType,
+    seed: Option<u64>,
+    distribution: F,
+}
+
+impl LtEncoder<IdealSoliton> {
+    /// A builder for an encoder with blocks of `blocksize` bytes. By default it
+    /// is a random encoder with the ideal soliton distribution and a random seed.
+    pub fn builder(blocksize: usize) -> LtEncoderBuilder {
+        LtEncoderBuilder {
+            blocksize,
+            encodertype: EncoderType::Random,
+            seed: None,
+            distribution: IdealSoliton::new,
+        }
+    }
+}
+
+impl<F> LtEncoderBuilder<F> {
+    pub fn encodertype(self, encodertype: EncoderType) -> LtEncoderBuilder<F> {
+        LtEncoderBuilder {
+            encodertype,
+            ..self
+        }
+    }
+
+    /// Fixes the droplet stream, the same seed gives the same droplets
+    pub fn seed(self, seed: u64) -> LtEncoderBuilder<F> {
+        LtEncoderBuilder {
+            seed: Some(seed),
+            ..self
+        }
+    }
+
+    /// Sets the function which creates the distribution from the number of blocks and the seed
+    pub fn distribution<G, S>(self, distribution: G) -> LtEncoderBuilder<G>
+    where
+        G: FnOnce(usize, u64) -> S,
+        S: Soliton,
+    {
+        LtEncoderBuilder {
+            blocksize: self.blocksize,
+            encodertype: self.encodertype,
+            seed: self.seed,
+            distribution,
+        }
+    }
+
+    /// Builds the encoder over an object in memory
+    pub fn build<S>(self, data: Vec<u8>) -> LtEncoder<S>
+    where
+        F: FnOnce(usize, u64) -> S,
+        S: Soliton,
+    {
+        let len = data.len();
+        self.build_with_source(data, len)
+    }
+
+    /// Builds the encoder which reads the source blocks from `reader` when they are needed
+    pub fn build_from_reader<S, R>(self, reader: R) -> io::Result<LtEncoder<S, SeekSource<R>>>
+    where
+        F: FnOnce(usize, u64) -> S,
+        S: Soliton,
+        R: Read + Seek,
+    {
+        let (source, len) = SeekSource::new(reader)?;
+        Ok(self.build_with_source(source, len))
+    }
+
+    /// Builds the encoder over a `BlockSource` which holds an object of `len` bytes
+    pub fn build_with_source<S, B>(self, source: B, len: usize) -> LtEncoder<S, B>
+    where
+        F: FnOnce(usize, u64) -> S,
+        S: Soliton,
+        B: BlockSource,
+    {
+        let seed = self.seed.unwrap_or_else(rand::random);
+        let sol = (self.distribution)(cnt_blocks(len, self.blocksize), seed);
+        LtEncoder::with_distribution(source, len, self.blocksize, self.encodertype, seed, sol)
+    }
+}
+
+impl<S: Soliton, B: BlockSource> LtEncoder<S, B> {
+    /// Constructs a new encoder which draws the degrees from `sol`.
+    /// The decoder does not need to know the distribution.
+    pub fn with_distribution(
+        source: B,
+        len: usize,
+        blocksize: usize,
+        encodertype: EncoderType,
+        seed: u64,
+        sol: S,
+    ) -> LtEncoder<S, B> {
+        let cnt_blocks = cnt_blocks(len, blocksize);
+        // every source block is sent once, then the repair droplets follow
+        let cnt_systematic = match encodertype {
+            EncoderType::Systematic => cnt_blocks,
+            EncoderType::Random => 0,
+        };
+        LtEncoder {
+            source,
+            len,
+            blocksize,
+            seed,
+            sol,
+            cnt: 0,
+            cnt_systematic,
+        }
+    }
+
+    /// The seed which determines the droplet stream
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Continues the stream at droplet `cnt`, the droplets before are not generated
+    pub fn seek(&mut self, cnt: usize) {
+        self.cnt = cnt;
+    }
+
+    /// The length of the object in bytes
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the object is empty
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn blocksize(&self) -> usize {
+        self.blocksize
+    }
+
+    pub fn cnt_blocks(&self) -> usize {
+        cnt_blocks(self.len, self.blocksize)
+    }
+}
+
+/// Draws `degree` distinct blocks out of `cnt_blocks`, see `prng` for the algorithm.
+/// The encoder and the decoder derive the same neighbours from `seed`, a
+/// degree larger than `cnt_blocks` yields every block.
+pub fn get_sample_from_rng_by_seed(
+    seed: u64,
+    cnt_blocks: usize,
+    degree: usize,
+) -> impl Iterator<Item = usize> {
+    Prng::new(seed).sample(cnt_blocks, degree).into_iter()
+}
+
+impl<S: Soliton, B> LtEncoder<S, B> {
+    /// The kind of droplet `idx` and the blocks it combines. It only depends on
+    /// the index, so droplets can be generated in any order.
+    fn plan(&self, idx: usize) -> (DropType, Vec<usize>) {
+        if idx < self.cnt_systematic {
+            return (DropType::Edges(idx), vec![idx]);
+        }
+        let cnt_blocks = cnt_blocks(self.len, self.blocksize);
+        let mut rng = droplet_rng(self.seed, idx);
+        let degree = self.sol.degree(rng.next_f64()).min(cnt_blocks);
+        let seed = rng.next_u64();
+        let sample = get_sample_from_rng_by_seed(seed, cnt_blocks, degree).collect();
+        (DropType::Seeded(seed, degree), sample)
+    }
+}
+
+/// The XOR of the source blocks `blocks`
+fn payload<B: BlockSource>(
+    source: &mut B,
+    len: usize,
+    blocksize: usize,
+    blocks: &[usize],
+) -> io::Result<Vec<u8>> {
+    let mut r = vec![0; blocksize];
+    if let [idx] = blocks[..] {
+        read_block(source, idx, len, &mut r)?;
+        return Ok(r);
+    }
+    let mut block = vec![0; blocksize];
+    for idx in blocks {
+        read_block(source, *idx, len, &mut block)?;
+        xor_into(&mut r, &block);
+    }
+    Ok(r)
+}
+
+impl<S: Soliton, B: BlockSource> LtEncoder<S, B> {
+    /// The droplets `range` of the stream, the same `next` returns at these
+    /// positions. The position of `next` does not change.
+    pub fn encode_range(&mut self, range: Range<usize>) -> io::Result<Vec<Droplet>> {
+        range
+            .map(|idx| {
+                let (droptype, blocks) = self.plan(idx);
+                let data = payload(&mut self.source, self.len, self.blocksize, &blocks)?;
+                Ok(Droplet::new(droptype, data))
+            })
+            .collect()
+    }
+}
+
+#[cfg(feature = "parallel")]
+impl<S: Soliton + Sync, B: AsRef<[u8]> + Sync> LtEncoder<S, B> {
+    /// Like `encode_range`, but generates the droplets on the rayon thread pool.
+    /// The source has to be in memory.
+    pub fn par_encode_range(&self, range: Range<usize>) -> Vec<Droplet> {
+        use rayon::prelude::*;
+
+        range
+            .into_par_iter()
+            .map(|idx| {
+                let (droptype, blocks) = self.plan(idx);
+                let mut source = self.source.as_ref();
+                let data = payload(&mut source, self.len, self.blocksize, &blocks)
+                    .expect("the blocks lie within the object");
+                Droplet::new(droptype, data)
+            })
+            .collect()
+    }
+}
+
+impl<S: Soliton, B: BlockSource> Encoder for LtEncoder<S, B> {
+    fn next(&mut self) -> Droplet {
+        self.try_next().expect("reading the source failed")
+    }
+
+    fn try_next(&mut self) -> io::Result<Droplet> {
+        let (droptype, blocks) = self.plan(self.cnt);
+        let data = payload(&mut self.source, self.len, self.blocksize, &blocks)?;
+        self.cnt += 1;
+        Ok(Droplet::new(droptype, data))
+    }
+}
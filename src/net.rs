@@ -0,0 +1,398 @@
+//! Sending and receiving droplets over UDP, enabled with the `net` feature.
+//!
+//! A `Sender` pushes the droplets of an `Encoder` to a peer at a fixed rate
+//! until the `Receiver` acknowledges the object, or a packet budget is spent.
+//! The receiver keeps acknowledging late droplets for a while after it
+//! finished, so a lost acknowledgement does not keep the sender busy.
+//! With an `AuthKey` the acknowledgements are authenticated as well.
+
+use crate::{
+    auth::{AuthKey, TAG_LEN},
+    decoder::{Decoder, Statistics},
+    droplet::Droplet,
+    encoder::Encoder,
+    types::{CatchResult, DecoderMode, DropType},
+    wire::{Limits, ObjectParams},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    io,
+    net::{SocketAddr, UdpSocket},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Prefix of the datagram which acknowledges a complete object
+const ACK_MAGIC: [u8; 8] = *b"FNTNDONE";
+
+/// Size of an acknowledgement, the magic followed by the object id.
+/// With a key, the tag of both follows.
+const ACK_LEN: usize = 16;
+
+/// The subset of `UdpSocket` used by `Sender` and `Receiver`
+pub trait DatagramSocket {
+    fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize>;
+
+    fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)>;
+
+    /// `None` blocks until a datagram arrives
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()>;
+}
+
+impl DatagramSocket for UdpSocket {
+    fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        UdpSocket::send_to(self, buf, addr)
+    }
+
+    fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        UdpSocket::recv_from(self, buf)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        UdpSocket::set_read_timeout(self, timeout)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        UdpSocket::set_nonblocking(self, nonblocking)
+    }
+}
+
+/// Drops a fraction of the outgoing datagrams, to test lossy links
+pub struct LossySocket<S> {
+    inner: S,
+    loss: f64,
+    rng: RefCell<StdRng>,
+}
+
+impl<S: DatagramSocket> LossySocket<S> {
+    /// Drops every datagram sent with probability `loss`, decided by an rng seeded with `seed`
+    pub fn new(inner: S, loss: f64, seed: u64) -> LossySocket<S> {
+        LossySocket {
+            inner,
+            loss,
+            rng: RefCell::new(StdRng::seed_from_u64(seed)),
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.inner
+    }
+}
+
+impl<S: DatagramSocket> DatagramSocket for LossySocket<S> {
+    fn send_to(&self, buf: &[u8], addr: SocketAddr) -> io::Result<usize> {
+        if self.rng.borrow_mut().gen::<f64>() < self.loss {
+            // pretend it was sent, like the network losing it
+            return Ok(buf.len());
+        }
+        self.inner.send_to(buf, addr)
+    }
+
+    fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.inner.recv_from(buf)
+    }
+
+    fn set_read_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.inner.set_read_timeout(timeout)
+    }
+
+    fn set_nonblocking(&self, nonblocking: bool) -> io::Result<()> {
+        self.inner.set_nonblocking(nonblocking)
+    }
+}
+
+fn ack_packet(object_id: u64, key: Option<&AuthKey>) -> Vec<u8> {
+    let mut ack = Vec::with_capacity(ACK_LEN + TAG_LEN);
+    ack.extend_from_slice(&ACK_MAGIC);
+    ack.extend_from_slice(&object_id.to_be_bytes());
+    if let Some(key) = key {
+        let tag = key.tag(&ack, &[]);
+        ack.extend_from_slice(&tag);
+    }
+    ack
+}
+
+/// The object id of an acknowledgement, `None` if it is not one or its tag
+/// does not match `key`
+fn parse_ack(packet: &[u8], key: Option<&AuthKey>) -> Option<u64> {
+    let tag_len = if key.is_some() { TAG_LEN } else { 0 };
+    if packet.len() != ACK_LEN + tag_len || packet[..8] != ACK_MAGIC {
+        return None;
+    }
+    let (ack, tag) = packet.split_at(ACK_LEN);
+    if let Some(key) = key {
+        if !key.verify(ack, &[], tag) {
+            return None;
+        }
+    }
+    let mut id = [0; 8];
+    id.copy_from_slice(&ack[8..]);
+    Some(u64::from_be_bytes(id))
+}
+
+fn is_timeout(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::WouldBlock || err.kind() == io::ErrorKind::TimedOut
+}
+
+/// Outcome of `Sender::send`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SendReport {
+    /// Number of droplets handed to the socket
+    pub cnt_sent: usize,
+    /// Whether the receiver acknowledged the object
+    pub acknowledged: bool,
+}
+
+/// Sends the droplets of one object to a receiver
+pub struct Sender<S> {
+    socket: S,
+    dest: SocketAddr,
+    params: ObjectParams,
+    interval: Duration,
+    auth_key: Option<AuthKey>,
+}
+
+impl<S: DatagramSocket> Sender<S> {
+    /// Sends at most `packets_per_second` droplets of the object described
+    /// by `params` to `dest`, `0` disables the rate limit.
+    pub fn new(
+        socket: S,
+        dest: SocketAddr,
+        params: ObjectParams,
+        packets_per_second: u32,
+    ) -> Sender<S> {
+        let interval = if packets_per_second == 0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs(1) / packets_per_second
+        };
+        Sender {
+            socket,
+            dest,
+            params,
+            interval,
+            auth_key: None,
+        }
+    }
+
+    /// Authenticates every droplet with `key`, and only accepts
+    /// acknowledgements authenticated with it
+    pub fn set_auth_key(&mut self, key: AuthKey) {
+        self.auth_key = Some(key);
+    }
+
+    /// Sends droplets of `enc` until the receiver acknowledges the object,
+    /// or `max_packets` droplets are sent.
+    pub fn send<E: Encoder>(&self, enc: &mut E, max_packets: usize) -> io::Result<SendReport> {
+        self.socket.set_nonblocking(true)?;
+        let start = Instant::now();
+        let mut cnt_sent = 0;
+        while cnt_sent < max_packets {
+            if self.poll_ack()? {
+                return Ok(SendReport {
+                    cnt_sent,
+                    acknowledged: true,
+                });
+            }
+
+            let drop = enc.try_next()?;
+            let packet = match self.auth_key {
+                Some(ref key) => drop.to_bytes_authenticated(&self.params, key),
+                None => drop.to_bytes(&self.params),
+            };
+            self.socket.send_to(&packet, self.dest)?;
+            cnt_sent += 1;
+
+            let next_slot = start + self.interval * u32::try_from(cnt_sent).unwrap_or(u32::MAX);
+            let now = Instant::now();
+            if next_slot > now {
+                thread::sleep(next_slot - now);
+            }
+        }
+        Ok(SendReport {
+            cnt_sent,
+            acknowledged: self.poll_ack()?,
+        })
+    }
+
+    /// Reads all pending datagrams, returns whether one acknowledged the object
+    fn poll_ack(&self) -> io::Result<bool> {
+        let mut buf = [0; ACK_LEN + TAG_LEN + 1];
+        let mut acknowledged = false;
+        loop {
+            match self.socket.recv_from(&mut buf) {
+                Ok((n, from)) => {
+                    let ack = parse_ack(&buf[..n], self.auth_key.as_ref());
+                    if from == self.dest && ack == Some(self.params.object_id) {
+                        acknowledged = true;
+                    }
+                }
+                Err(ref err) if is_timeout(err) => return Ok(acknowledged),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.socket
+    }
+}
+
+/// Receives one object and acknowledges it to the sender
+///
+/// The first packet which parses decides which object is received. Without
+/// `set_auth_key` anybody who can reach the socket can pick it, so only skip
+/// the key on trusted networks. Objects beyond the `Limits`, by default 1 GiB,
+/// are ignored.
+pub struct Receiver<S> {
+    socket: S,
+    idle_timeout: Duration,
+    linger: Duration,
+    mode: DecoderMode,
+    auth_key: Option<AuthKey>,
+    limits: Limits,
+}
+
+impl<S: DatagramSocket> Receiver<S> {
+    /// Gives up once no droplet arrived for `idle_timeout`. After the object
+    /// is complete, late droplets are acknowledged until none arrived for `linger`.
+    /// Datagrams which are not droplets of the object do not count.
+    pub fn new(socket: S, idle_timeout: Duration, linger: Duration) -> Receiver<S> {
+        Receiver::with_mode(socket, idle_timeout, linger, DecoderMode::Peeling)
+    }
+
+    /// Like `new`, but decodes with the given mode
+    pub fn with_mode(
+        socket: S,
+        idle_timeout: Duration,
+        linger: Duration,
+        mode: DecoderMode,
+    ) -> Receiver<S> {
+        Receiver {
+            socket,
+            idle_timeout,
+            linger,
+            mode,
+            auth_key: None,
+            limits: Limits::default(),
+        }
+    }
+
+    /// Ignores every packet which is not authenticated with `key`, and
+    /// authenticates the acknowledgements with it
+    pub fn set_auth_key(&mut self, key: AuthKey) {
+        self.auth_key = Some(key);
+    }
+
+    /// Ignores the packets of objects beyond `limits`, the object is held in memory
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    fn parse(&self, packet: &[u8]) -> Option<(Droplet, ObjectParams)> {
+        let (drop, params) = match self.auth_key {
+            Some(ref key) => Droplet::from_bytes_authenticated(packet, key).ok()?,
+            None => Droplet::from_bytes(packet).ok()?,
+        };
+        self.limits.check(&params).ok()?;
+        Some((drop, params))
+    }
+
+    /// Waits for the next datagram, fails with a timeout at `deadline`
+    fn recv_until(&self, buf: &mut [u8], deadline: Instant) -> io::Result<(usize, SocketAddr)> {
+        let now = Instant::now();
+        if deadline <= now {
+            return Err(io::ErrorKind::TimedOut.into());
+        }
+        self.socket.set_read_timeout(Some(deadline - now))?;
+        self.socket.recv_from(buf)
+    }
+
+    /// Receives the droplets of the first object which arrives, and returns it
+    /// once it is complete. Packets which do not parse, are not authenticated or
+    /// exceed the limits, and droplets of other objects, are ignored. Fails with
+    /// `TimedOut` if the sender goes quiet.
+    pub fn receive(&self) -> io::Result<(ObjectParams, Vec<u8>, Statistics)> {
+        self.socket.set_nonblocking(false)?;
+
+        let mut buf = vec![0; 65536];
+        let mut object: Option<(ObjectParams, Decoder)> = None;
+        let mut deadline = Instant::now() + self.idle_timeout;
+        let (params, data, stats) = loop {
+            let (n, from) = match self.recv_until(&mut buf, deadline) {
+                Ok(received) => received,
+                Err(ref err) if is_timeout(err) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::TimedOut,
+                        "no droplet arrived before the idle timeout",
+                    ))
+                }
+                Err(err) => return Err(err),
+            };
+            let (drop, rx_params) = match self.parse(&buf[..n]) {
+                Some(parsed) => parsed,
+                None => continue,
+            };
+            if let DropType::Esi(_) = drop.droptype {
+                continue;
+            }
+            if rx_params.len == 0 {
+                continue;
+            }
+
+            let mode = self.mode;
+            let (params, dec) = object.get_or_insert_with(|| {
+                (
+                    rx_params,
+                    Decoder::with_mode(rx_params.len, rx_params.blocksize, mode),
+                )
+            });
+            if *params != rx_params {
+                continue;
+            }
+            match dec.catch(drop) {
+                Ok(CatchResult::Finished(data, stats)) => {
+                    let ack = ack_packet(params.object_id, self.auth_key.as_ref());
+                    self.socket.send_to(&ack, from)?;
+                    break (*params, data, stats);
+                }
+                Ok(_) => deadline = Instant::now() + self.idle_timeout,
+                Err(_) => {}
+            }
+        };
+
+        self.linger(params.object_id, &mut buf)?;
+        Ok((params, data, stats))
+    }
+
+    /// Acknowledges the droplets of the completed object until the sender stops
+    fn linger(&self, object_id: u64, buf: &mut [u8]) -> io::Result<()> {
+        if self.linger == Duration::from_secs(0) {
+            return Ok(());
+        }
+        let ack = ack_packet(object_id, self.auth_key.as_ref());
+        let mut deadline = Instant::now() + self.linger;
+        loop {
+            match self.recv_until(buf, deadline) {
+                Ok((n, from)) => {
+                    if let Some((_, params)) = self.parse(&buf[..n]) {
+                        if params.object_id == object_id {
+                            self.socket.send_to(&ack, from)?;
+                            deadline = Instant::now() + self.linger;
+                        }
+                    }
+                }
+                Err(ref err) if is_timeout(err) => return Ok(()),
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    pub fn into_inner(self) -> S {
+        self.socket
+    }
+}
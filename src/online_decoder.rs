@@ -0,0 +1,102 @@
+use crate::{
+    decoder::{DecodeError, Decoder},
+    droplet::Droplet,
+    online_encoder::{cnt_aux_blocks, outer_code},
+    types::{CatchResult, DecoderMode},
+    wire::cnt_blocks,
+};
+
+/// Decoder for Online codes
+///
+/// The droplets are peeled over the composite message. The equations of the outer code
+/// connect the auxiliary blocks with the source blocks, so recovered auxiliary blocks
+/// help to recover the remaining source blocks.
+pub struct OnlineDecoder {
+    decoder: Decoder,
+}
+
+impl OnlineDecoder {
+    /// Creates a new Decoder for Online codes.
+    /// The parameters must match those of the `OnlineEncoder`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate fountaincode;
+    ///
+    /// fn main() {
+    ///     use self::fountaincode::online_encoder::OnlineEncoder;
+    ///     use self::fountaincode::online_decoder::OnlineDecoder;
+    ///     use self::fountaincode::encoder::Encoder;
+    ///     use self::fountaincode::types::*;
+    ///     use self::rand::{thread_rng, Rng};
+    ///     use rand::distributions::Alphanumeric;
+    ///
+    ///     let s:String = thread_rng().sample_iter(Alphanumeric).take(1024).collect();
+    ///     let buf = s.into_bytes();
+    ///     let to_compare = buf.clone();
+    ///     let length = buf.len();
+    ///
+    ///     let mut enc = OnlineEncoder::new(buf, 64, EncoderType::Random, 0.01, 3);
+    ///     let mut dec = OnlineDecoder::new(length, 64, 0.01, 3, enc.outer_seed());
+    ///
+    ///     loop {
+    ///         let drop = enc.next();
+    ///         match dec.catch(drop).unwrap() {
+    ///             CatchResult::Missing(stats) => {
+    ///                 println!("Missing blocks {:?}", stats);
+    ///             }
+    ///             CatchResult::Finished(data, stats) => {
+    ///                 assert_eq!(to_compare, data);
+    ///                 println!("Finished, stats: {:?}", stats);
+    ///                 return
+    ///             }
+    ///             CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn new(
+        len: usize,
+        blocksize: usize,
+        epsilon: f64,
+        q: usize,
+        outer_seed: u64,
+    ) -> OnlineDecoder {
+        OnlineDecoder::with_mode(len, blocksize, epsilon, q, outer_seed, DecoderMode::Peeling)
+    }
+
+    /// Creates a new Decoder for Online codes which decodes with the given mode
+    pub fn with_mode(
+        len: usize,
+        blocksize: usize,
+        epsilon: f64,
+        q: usize,
+        outer_seed: u64,
+        mode: DecoderMode,
+    ) -> OnlineDecoder {
+        let cnt_blocks = cnt_blocks(len, blocksize);
+        let cnt_aux = cnt_aux_blocks(cnt_blocks, epsilon, q);
+        let mut decoder = Decoder::with_aux_blocks(len, blocksize, cnt_aux, mode);
+
+        // every auxiliary block xor its source blocks is zero
+        for (aux, mut edges) in outer_code(cnt_blocks, cnt_aux, q, outer_seed)
+            .into_iter()
+            .enumerate()
+        {
+            edges.push(cnt_blocks + aux);
+            decoder
+                .add_equation(edges, vec![0; blocksize])
+                .expect("writing to memory does not fail");
+        }
+        OnlineDecoder { decoder }
+    }
+
+    /// Catches a Droplet
+    /// When it is possible to reconstruct a set, the bytes are returned.
+    /// The statistics only count the source blocks.
+    pub fn catch(&mut self, drop: Droplet) -> Result<CatchResult, DecodeError> {
+        self.decoder.catch(drop)
+    }
+}
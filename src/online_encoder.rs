@@ -0,0 +1,189 @@
+use crate::{
+    droplet::Droplet,
+    encoder::Encoder,
+    lt_encoder::get_sample_from_rng_by_seed,
+    online_soliton::OnlineSoliton,
+    prng::Prng,
+    soliton::Soliton,
+    types::{DropType, EncoderType},
+    wire::cnt_blocks,
+    xor::xor_into,
+};
+use rand::{
+    rngs::StdRng,
+    {Rng, SeedableRng},
+};
+use std::cmp;
+
+/// Encoder for Online codes
+///
+/// The source blocks are first extended by a few auxiliary blocks of an outer code,
+/// every source block is added to `q` of them. The droplets are then produced over
+/// this composite message, with the constant average degree `OnlineSoliton`.
+#[derive(Clone)]
+pub struct OnlineEncoder {
+    data: Vec<u8>,
+    blocksize: usize,
+    rng: StdRng,
+    cnt_blocks: usize,
+    cnt_composite: usize,
+    sol: OnlineSoliton,
+    outer_seed: u64,
+    pub cnt: usize,
+    encodertype: EncoderType,
+}
+
+impl OnlineEncoder {
+    /// Constructs a new encoder for Online codes.
+    /// `epsilon` is the quality parameter of the code and `q` the number of
+    /// auxiliary blocks each source block is added to, the paper suggests 0.01 and 3.
+    ///
+    /// The decoder needs the same parameters and the seed of the outer code,
+    /// see `outer_seed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `data` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate fountaincode;
+    ///
+    /// fn main() {
+    ///     use fountaincode::online_encoder::OnlineEncoder;
+    ///     use fountaincode::encoder::Encoder;
+    ///     use fountaincode::types::EncoderType;
+    ///     use self::rand::{thread_rng, Rng};
+    ///     use rand::distributions::Alphanumeric;
+    ///
+    ///     let s: String = thread_rng().sample_iter(Alphanumeric).take(1024).collect();
+    ///     let buf = s.into_bytes();
+    ///
+    ///     let mut enc = OnlineEncoder::new(buf, 64, EncoderType::Random, 0.01, 3);
+    ///
+    ///     for i in 1..10 {
+    ///         println!("droplet {:?}: {:?}", i, enc.next());
+    ///     }
+    /// }
+    /// ```
+    pub fn new(
+        data: Vec<u8>,
+        blocksize: usize,
+        encodertype: EncoderType,
+        epsilon: f64,
+        q: usize,
+    ) -> OnlineEncoder {
+        OnlineEncoder::with_seed(data, blocksize, encodertype, epsilon, q, rand::random())
+    }
+
+    /// Constructs a new encoder whose outer code and droplet stream are determined by `seed`
+    pub fn with_seed(
+        data: Vec<u8>,
+        blocksize: usize,
+        encodertype: EncoderType,
+        epsilon: f64,
+        q: usize,
+        seed: u64,
+    ) -> OnlineEncoder {
+        let mut rng = StdRng::seed_from_u64(seed);
+
+        let cnt_blocks = cnt_blocks(data.len(), blocksize);
+        assert!(
+            cnt_blocks > 0,
+            "Online codes can not encode an empty object"
+        );
+        let cnt_aux = cnt_aux_blocks(cnt_blocks, epsilon, q);
+        let cnt_composite = cnt_blocks + cnt_aux;
+        let outer_seed = rng.gen::<u64>();
+
+        let mut composite = data;
+        composite.resize(cnt_composite * blocksize, 0);
+        for (aux, sources) in outer_code(cnt_blocks, cnt_aux, q, outer_seed)
+            .iter()
+            .enumerate()
+        {
+            // the sources are message blocks, which lie before the auxiliary ones
+            let (message, aux_blocks) = composite.split_at_mut(cnt_blocks * blocksize);
+            let dst = &mut aux_blocks[aux * blocksize..(aux + 1) * blocksize];
+            for src in sources {
+                xor_into(dst, &message[src * blocksize..(src + 1) * blocksize]);
+            }
+        }
+
+        let sol = OnlineSoliton::new(epsilon, rng.gen::<u64>());
+        OnlineEncoder {
+            data: composite,
+            blocksize,
+            rng,
+            cnt_blocks,
+            cnt_composite,
+            sol,
+            outer_seed,
+            cnt: 0,
+            encodertype,
+        }
+    }
+
+    /// The seed of the outer code, the decoder needs it to rebuild the auxiliary blocks
+    pub fn outer_seed(&self) -> u64 {
+        self.outer_seed
+    }
+}
+
+/// The number of auxiliary blocks for `cnt_blocks` source blocks, 0.55 * q * epsilon * n
+pub fn cnt_aux_blocks(cnt_blocks: usize, epsilon: f64, q: usize) -> usize {
+    let cnt = (0.55 * q as f64 * epsilon * cnt_blocks as f64).ceil() as usize;
+    cmp::max(cnt, 1)
+}
+
+/// For every auxiliary block, the source blocks it is composed of.
+/// Each source block is added to `q` distinct auxiliary blocks.
+pub(crate) fn outer_code(
+    cnt_blocks: usize,
+    cnt_aux: usize,
+    q: usize,
+    seed: u64,
+) -> Vec<Vec<usize>> {
+    let mut rng = Prng::new(seed);
+    let mut aux_blocks = vec![Vec::new(); cnt_aux];
+    for src in 0..cnt_blocks {
+        for aux in rng.sample(cnt_aux, q) {
+            aux_blocks[aux].push(src);
+        }
+    }
+    aux_blocks
+}
+
+impl Encoder for OnlineEncoder {
+    fn next(&mut self) -> Droplet {
+        let drop = match self.encodertype {
+            EncoderType::Random => {
+                let degree = cmp::min(self.sol.next(), self.cnt_composite);
+                let seed = self.rng.gen::<u64>();
+                let sample = get_sample_from_rng_by_seed(seed, self.cnt_composite, degree);
+                let mut r = vec![0; self.blocksize];
+
+                for k in sample {
+                    xor_into(
+                        &mut r,
+                        &self.data[k * self.blocksize..(k + 1) * self.blocksize],
+                    );
+                }
+                Droplet::new(DropType::Seeded(seed, degree), r)
+            }
+            EncoderType::Systematic => {
+                let idx = self.cnt % self.cnt_blocks;
+                let r = self.data[idx * self.blocksize..(idx + 1) * self.blocksize].to_vec();
+                if self.cnt + 1 >= self.cnt_blocks {
+                    self.encodertype = EncoderType::Random;
+                }
+                Droplet::new(DropType::Edges(idx), r)
+            }
+        };
+
+        self.cnt += 1;
+        drop
+    }
+}
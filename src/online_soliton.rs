@@ -0,0 +1,60 @@
+use crate::soliton::Soliton;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// The degree distribution of Online codes, see Maymounkov, "Online Codes".
+/// The average degree is constant, it does not grow with the number of blocks.
+#[derive(Debug, Clone)]
+pub struct OnlineSoliton {
+    cdf: Vec<f64>,
+    rng: StdRng,
+}
+
+impl OnlineSoliton {
+    /// Creates the distribution for the quality parameter `epsilon`,
+    /// the maximum degree is F = ceil(ln(epsilon^2 / 4) / ln(1 - epsilon / 2)).
+    ///
+    /// # Panics
+    ///
+    /// Panics if `epsilon` is not in (0, 1).
+    pub fn new(epsilon: f64, seed: u64) -> OnlineSoliton {
+        assert!(epsilon > 0.0 && epsilon < 1.0, "epsilon must be in (0, 1)");
+        let f = max_degree(epsilon);
+        let rho1 = 1.0 - (1.0 + 1.0 / f as f64) / (1.0 + epsilon);
+
+        let mut cdf = Vec::with_capacity(f);
+        let mut sum = rho1;
+        cdf.push(sum);
+        for i in 2..=f {
+            let i = i as f64;
+            sum += (1.0 - rho1) * f as f64 / ((f as f64 - 1.0) * i * (i - 1.0));
+            cdf.push(sum);
+        }
+        OnlineSoliton {
+            cdf,
+            rng: SeedableRng::seed_from_u64(seed),
+        }
+    }
+
+    /// The maximum degree F
+    pub fn max_degree(&self) -> usize {
+        self.cdf.len()
+    }
+}
+
+fn max_degree(epsilon: f64) -> usize {
+    let f = ((epsilon * epsilon / 4.0).ln() / (1.0 - epsilon / 2.0).ln()).ceil() as usize;
+    f.max(2)
+}
+
+impl Soliton for OnlineSoliton {
+    fn next(&mut self) -> usize {
+        let y = self.rng.gen::<f64>();
+        self.degree(y)
+    }
+
+    fn degree(&self, u: f64) -> usize {
+        // the probabilities sum up to one, rounding errors end in the last degree
+        let idx = self.cdf.iter().position(|p| u < *p);
+        idx.unwrap_or(self.cdf.len() - 1) + 1
+    }
+}
@@ -0,0 +1,311 @@
+use crate::custom_distribution::{CustomDistribution, DistributionError};
+use crate::decoder::Decoder;
+use crate::encoder::Encoder;
+use crate::lt_encoder::LtEncoder;
+use crate::robust_soliton::RobustSoliton;
+use crate::soliton::Soliton;
+use crate::types::{CatchResult, DecoderMode};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::{error, fmt, str::FromStr};
+
+/// The values of `c` searched by `optimize_robust`
+const GRID_C: [f32; 7] = [0.01, 0.02, 0.03, 0.05, 0.1, 0.2, 0.5];
+/// The values of `delta` searched by `optimize_robust`
+const GRID_DELTA: [f32; 5] = [0.01, 0.05, 0.1, 0.5, 1.0];
+
+/// The parameters of `RobustEncoder::new`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RobustParams {
+    pub c: f32,
+    pub delta: f32,
+    pub spike: Option<usize>,
+}
+
+impl RobustParams {
+    /// The distribution for `k` blocks
+    pub fn soliton(&self, k: usize, seed: u64) -> RobustSoliton {
+        RobustSoliton::new(k, seed, self.c, self.spike, self.delta)
+    }
+}
+
+/// A tuned distribution, written as one line of text so it can be shipped
+/// with an application, e.g. `robust c=0.05 delta=0.5 spike=auto` or
+/// `custom 0 0.1 0.5 0.4`, the probabilities of the degrees 0, 1, 2, ...
+#[derive(Debug, Clone, PartialEq)]
+pub enum Preset {
+    Robust(RobustParams),
+    Custom(Vec<f64>),
+}
+
+/// Errors returned when a preset can not be parsed
+#[derive(Debug, Clone, PartialEq)]
+pub enum PresetError {
+    /// The line does not follow the format
+    Syntax(String),
+    /// The probabilities of a custom preset are not a distribution
+    Distribution(DistributionError),
+}
+
+impl fmt::Display for PresetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PresetError::Syntax(msg) => write!(f, "invalid preset: {}", msg),
+            PresetError::Distribution(err) => write!(f, "invalid preset: {}", err),
+        }
+    }
+}
+
+impl error::Error for PresetError {}
+
+impl fmt::Display for Preset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Preset::Robust(params) => {
+                write!(f, "robust c={} delta={} spike=", params.c, params.delta)?;
+                match params.spike {
+                    Some(m) => write!(f, "{}", m),
+                    None => write!(f, "auto"),
+                }
+            }
+            Preset::Custom(probabilities) => {
+                write!(f, "custom")?;
+                for p in probabilities {
+                    write!(f, " {}", p)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl FromStr for Preset {
+    type Err = PresetError;
+
+    fn from_str(s: &str) -> Result<Preset, PresetError> {
+        let syntax = |msg: &str| PresetError::Syntax(msg.to_string());
+        let mut words = s.split_whitespace();
+        match words.next() {
+            Some("robust") => {
+                let (mut c, mut delta, mut spike) = (None, None, None);
+                for word in words {
+                    let (key, value) = word.split_once('=').ok_or_else(|| syntax(word))?;
+                    match key {
+                        "c" => c = Some(value.parse().map_err(|_| syntax(word))?),
+                        "delta" => delta = Some(value.parse().map_err(|_| syntax(word))?),
+                        "spike" if value == "auto" => spike = Some(None),
+                        "spike" => spike = Some(Some(value.parse().map_err(|_| syntax(word))?)),
+                        _ => return Err(syntax(word)),
+                    }
+                }
+                Ok(Preset::Robust(RobustParams {
+                    c: c.ok_or_else(|| syntax("c is missing"))?,
+                    delta: delta.ok_or_else(|| syntax("delta is missing"))?,
+                    spike: spike.unwrap_or(None),
+                }))
+            }
+            Some("custom") => {
+                let probabilities = words
+                    .map(|word| word.parse().map_err(|_| syntax(word)))
+                    .collect::<Result<Vec<f64>, _>>()?;
+                CustomDistribution::new(&probabilities, 0).map_err(PresetError::Distribution)?;
+                Ok(Preset::Custom(probabilities))
+            }
+            Some(other) => Err(syntax(other)),
+            None => Err(syntax("the line is empty")),
+        }
+    }
+}
+
+/// What the optimizer minimizes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Objective {
+    Mean,
+    P95,
+}
+
+/// The overhead over many trials: the droplets sent until the object was
+/// decoded, lost ones included, divided by the number of blocks, minus one
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overhead {
+    pub mean: f64,
+    pub p95: f64,
+    /// Trials which did not finish within `Simulation::max_overhead`,
+    /// they count as `max_overhead`
+    pub cnt_failed: usize,
+}
+
+impl Overhead {
+    pub fn score(&self, objective: Objective) -> f64 {
+        match objective {
+            Objective::Mean => self.mean,
+            Objective::P95 => self.p95,
+        }
+    }
+}
+
+/// A set of parameters and how it performed
+#[derive(Debug, Clone)]
+pub struct Candidate<P> {
+    pub params: P,
+    pub overhead: Overhead,
+}
+
+/// Sends an object of `cnt_blocks` blocks over a channel which loses a
+/// fraction `loss` of the droplets, and decodes it with a `Decoder`
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub cnt_blocks: usize,
+    pub loss: f64,
+    pub trials: usize,
+    pub mode: DecoderMode,
+    /// A trial gives up after `cnt_blocks * (1 + max_overhead)` droplets
+    pub max_overhead: f64,
+    pub seed: u64,
+}
+
+impl Simulation {
+    pub fn new(cnt_blocks: usize, loss: f64) -> Simulation {
+        Simulation {
+            cnt_blocks: cnt_blocks.max(1),
+            loss,
+            trials: 100,
+            mode: DecoderMode::Peeling,
+            max_overhead: 4.0,
+            seed: 0,
+        }
+    }
+
+    pub fn with_trials(self, trials: usize) -> Simulation {
+        Simulation {
+            trials: trials.max(1),
+            ..self
+        }
+    }
+
+    pub fn with_mode(self, mode: DecoderMode) -> Simulation {
+        Simulation { mode, ..self }
+    }
+
+    pub fn with_seed(self, seed: u64) -> Simulation {
+        Simulation { seed, ..self }
+    }
+
+    /// Runs the trials with the distributions built by `make` from the seed of each trial.
+    /// Every evaluation sees the same droplet seeds and losses, which keeps
+    /// the comparison between distributions fair.
+    pub fn run<S: Soliton, F: FnMut(u64) -> S>(&self, mut make: F) -> Overhead {
+        let k = self.cnt_blocks;
+        let max_sent = (k as f64 * (1.0 + self.max_overhead)).ceil() as usize;
+        let mut overheads = Vec::with_capacity(self.trials);
+        let mut cnt_failed = 0;
+
+        for trial in 0..self.trials {
+            let seed = self.seed.wrapping_add(trial as u64);
+            let mut loss_rng: StdRng = SeedableRng::seed_from_u64(!seed);
+            // one byte per block, the content does not matter
+            let mut enc = LtEncoder::builder(1)
+                .seed(seed)
+                .distribution(|_, seed| make(seed))
+                .build(vec![0; k]);
+            let mut dec = Decoder::with_mode(k, 1, self.mode);
+
+            let mut cnt_sent = 0;
+            let finished = loop {
+                if cnt_sent == max_sent {
+                    break false;
+                }
+                let drop = enc.next();
+                cnt_sent += 1;
+                if loss_rng.gen::<f64>() < self.loss {
+                    continue;
+                }
+                match dec.catch(drop).expect("the encoder sends valid droplets") {
+                    CatchResult::Missing(_) => {}
+                    _ => break true,
+                }
+            };
+            if !finished {
+                cnt_failed += 1;
+            }
+            overheads.push(cnt_sent as f64 / k as f64 - 1.0);
+        }
+
+        overheads.sort_by(|a, b| a.partial_cmp(b).expect("overheads are finite"));
+        let p95_idx = ((overheads.len() as f64 * 0.95).ceil() as usize).max(1) - 1;
+        Overhead {
+            mean: overheads.iter().sum::<f64>() / overheads.len() as f64,
+            p95: overheads[p95_idx],
+            cnt_failed,
+        }
+    }
+}
+
+/// Evaluates a grid of `RobustSoliton` parameters, the best candidate comes first
+pub fn optimize_robust(sim: &Simulation, objective: Objective) -> Vec<Candidate<RobustParams>> {
+    let mut candidates = Vec::with_capacity(GRID_C.len() * GRID_DELTA.len());
+    for &c in GRID_C.iter() {
+        for &delta in GRID_DELTA.iter() {
+            let params = RobustParams {
+                c,
+                delta,
+                spike: None,
+            };
+            let overhead = sim.run(|seed| params.soliton(sim.cnt_blocks, seed));
+            candidates.push(Candidate { params, overhead });
+        }
+    }
+    sort_candidates(&mut candidates, objective);
+    candidates
+}
+
+/// Improves a table of probabilities, indexed by degree, by moving
+/// probability between degrees for `rounds` rounds and keeping the moves
+/// which lower the overhead.
+pub fn optimize_custom(
+    sim: &Simulation,
+    objective: Objective,
+    initial: &[f64],
+    rounds: usize,
+) -> Result<Candidate<Vec<f64>>, DistributionError> {
+    let evaluate = |probabilities: &[f64]| -> Result<Overhead, DistributionError> {
+        CustomDistribution::new(probabilities, 0)?;
+        Ok(sim.run(|seed| {
+            CustomDistribution::new(probabilities, seed).expect("the table was validated")
+        }))
+    };
+
+    let mut best = initial.to_vec();
+    best.resize(best.len().max(2), 0.0);
+    let mut best_overhead = evaluate(&best)?;
+    let mut rng: StdRng = SeedableRng::seed_from_u64(sim.seed);
+
+    for _ in 0..rounds {
+        let from = rng.gen_range(1, best.len());
+        let to = rng.gen_range(1, best.len());
+        if from == to || best[from] == 0.0 {
+            continue;
+        }
+        let mut probabilities = best.clone();
+        let amount = best[from] * rng.gen_range(0.1, 0.5);
+        probabilities[from] -= amount;
+        probabilities[to] += amount;
+
+        let overhead = evaluate(&probabilities)?;
+        if overhead.score(objective) < best_overhead.score(objective) {
+            best = probabilities;
+            best_overhead = overhead;
+        }
+    }
+    Ok(Candidate {
+        params: best,
+        overhead: best_overhead,
+    })
+}
+
+fn sort_candidates<P>(candidates: &mut [Candidate<P>], objective: Objective) {
+    candidates.sort_by(|a, b| {
+        let a = (a.overhead.cnt_failed, a.overhead.score(objective));
+        let b = (b.overhead.cnt_failed, b.overhead.score(objective));
+        a.partial_cmp(&b).expect("overheads are finite")
+    });
+}
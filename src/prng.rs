@@ -0,0 +1,101 @@
+//! The pseudo random number generator which maps droplet seeds to neighbours.
+//!
+//! Peers must derive the same neighbours from a seed, across versions of this
+//! crate, of `rand` and other implementations, so the generator is fixed:
+//!
+//! - `Prng::new(seed)` fills the state of xoshiro256** with the first four
+//!   outputs of splitmix64 started at `seed`.
+//! - `next_u64` is xoshiro256**, see <https://prng.di.unimi.it/xoshiro256starstar.c>.
+//! - `below(n)` draws `x = next_u64()` until `x >= (2^64 - n) mod n` and returns `x mod n`.
+//! - `next_f64` is `(next_u64() >> 11) * 2^-53`.
+//! - `sample(n, k)` is Floyd's algorithm: for `j` in `n - min(k, n)..n` it draws
+//!   `t = below(j + 1)` and appends `t`, or `j` if `t` was appended before.
+//!
+//! The neighbours of a droplet with seed `s` and degree `d` over `k` blocks are
+//! `Prng::new(s).sample(k, d)`. The test vectors are in `tests/prng_test.rs`.
+
+use std::collections::HashSet;
+
+/// Samples up to this size look up the drawn values in the sample itself, larger ones in a set
+const MAX_LINEAR_SAMPLE: usize = 32;
+
+/// xoshiro256** seeded with splitmix64
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Prng {
+    s: [u64; 4],
+}
+
+/// One step of splitmix64, advances `state` and returns the output
+pub fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Prng {
+    pub fn new(seed: u64) -> Prng {
+        let mut state = seed;
+        let mut s = [0; 4];
+        for word in s.iter_mut() {
+            *word = splitmix64(&mut state);
+        }
+        Prng { s }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// A uniform value in [0, 1) with 53 random bits
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+
+    /// A uniform value in [0, n), without modulo bias. `n` must not be zero.
+    pub fn below(&mut self, n: u64) -> u64 {
+        assert!(n > 0, "the range is empty");
+        let threshold = n.wrapping_neg() % n;
+        loop {
+            let x = self.next_u64();
+            if x >= threshold {
+                return x % n;
+            }
+        }
+    }
+
+    /// `k` distinct values in [0, n), all of them if `k >= n`
+    pub fn sample(&mut self, n: usize, k: usize) -> Vec<usize> {
+        let k = k.min(n);
+        let mut sample = Vec::with_capacity(k);
+        let mut drawn = HashSet::new();
+        for j in n - k..n {
+            let t = self.below(j as u64 + 1) as usize;
+            // j can not have been drawn yet, it is larger than every value drawn so far
+            let pick = if k <= MAX_LINEAR_SAMPLE {
+                if sample.contains(&t) {
+                    j
+                } else {
+                    t
+                }
+            } else if drawn.insert(t) {
+                t
+            } else {
+                drawn.insert(j);
+                j
+            };
+            sample.push(pick);
+        }
+        sample
+    }
+}
@@ -0,0 +1,640 @@
+//! Building blocks of RaptorQ (RFC 6330) shared by `RaptorQEncoder` and `RaptorQDecoder`.
+//!
+//! The object is encoded as a single source block without sub-blocking, so the
+//! number of source symbols is limited to `MAX_SOURCE_SYMBOLS`. The encoding symbol
+//! identifier (ESI) of every symbol is carried in `DropType::Esi`.
+
+use crate::raptorq_tables::{SYSTEMATIC_INDICES, V0, V1, V2, V3};
+use crate::xor::xor_into;
+use std::{cmp::Reverse, collections::BinaryHeap};
+
+/// Largest number of source symbols a single source block can hold (K'max)
+pub const MAX_SOURCE_SYMBOLS: usize = 56403;
+
+// Section 5.7: GF(256) with the irreducible polynomial x^8 + x^4 + x^3 + x^2 + 1
+const OCT_EXP: [u8; 510] = oct_exp();
+const OCT_LOG: [u8; 256] = oct_log();
+
+const fn oct_exp() -> [u8; 510] {
+    let mut table = [0; 510];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 510 {
+        table[i] = x as u8;
+        x <<= 1;
+        if x & 0x100 != 0 {
+            x ^= 0x11d;
+        }
+        i += 1;
+    }
+    table
+}
+
+const fn oct_log() -> [u8; 256] {
+    let exp = oct_exp();
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 255 {
+        table[exp[i] as usize] = i as u8;
+        i += 1;
+    }
+    table
+}
+
+fn oct_mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        OCT_EXP[OCT_LOG[a as usize] as usize + OCT_LOG[b as usize] as usize]
+    }
+}
+
+fn oct_div(a: u8, b: u8) -> u8 {
+    debug_assert!(b != 0);
+    if a == 0 {
+        0
+    } else {
+        OCT_EXP[OCT_LOG[a as usize] as usize + 255 - OCT_LOG[b as usize] as usize]
+    }
+}
+
+/// `dst += factor * src`, element wise over GF(256)
+fn add_mul_assign(dst: &mut [u8], src: &[u8], factor: u8) {
+    if factor == 0 {
+        return;
+    }
+    if factor == 1 {
+        xor_into(dst, src);
+        return;
+    }
+    let log_factor = OCT_LOG[factor as usize] as usize;
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        if *s != 0 {
+            *d ^= OCT_EXP[OCT_LOG[*s as usize] as usize + log_factor];
+        }
+    }
+}
+
+/// `dst = dst / divisor`, element wise over GF(256)
+fn div_assign(dst: &mut [u8], divisor: u8) {
+    if divisor == 1 {
+        return;
+    }
+    for d in dst.iter_mut() {
+        *d = oct_div(*d, divisor);
+    }
+}
+
+/// Rand[y, i, m] of section 5.3.5.1
+fn rand(y: u32, i: u32, m: u32) -> u32 {
+    let x0 = y.wrapping_add(i) % 256;
+    let x1 = (y >> 8).wrapping_add(i) % 256;
+    let x2 = (y >> 16).wrapping_add(i) % 256;
+    let x3 = (y >> 24).wrapping_add(i) % 256;
+    (V0[x0 as usize] ^ V1[x1 as usize] ^ V2[x2 as usize] ^ V3[x3 as usize]) % m
+}
+
+/// Deg[v] of section 5.3.5.2
+fn deg(v: u32, w: u32) -> u32 {
+    const F: [u32; 31] = [
+        0, 5243, 529_531, 704_294, 791_675, 844_104, 879_057, 904_023, 922_747, 937_311, 948_962,
+        958_494, 966_438, 973_160, 978_921, 983_914, 988_283, 992_138, 995_565, 998_631, 1_001_391,
+        1_003_887, 1_006_157, 1_008_229, 1_010_129, 1_011_876, 1_013_490, 1_014_983, 1_016_370,
+        1_017_662, 1_048_576,
+    ];
+    let d = F.iter().position(|&f| v < f).unwrap() as u32;
+    d.min(w - 2)
+}
+
+fn is_prime(n: usize) -> bool {
+    if n < 2 {
+        return false;
+    }
+    let mut i = 2;
+    while i * i <= n {
+        if n.is_multiple_of(i) {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Parameters derived from the number of source symbols, see section 5.3.3.3.
+#[derive(Debug, Clone)]
+pub(crate) struct Params {
+    /// Number of source symbols
+    pub k: usize,
+    /// Number of source symbols including padding symbols
+    pub k_prime: usize,
+    j: u32,
+    s: usize,
+    h: usize,
+    w: usize,
+    /// Number of intermediate symbols
+    pub l: usize,
+    p: usize,
+    p1: usize,
+}
+
+impl Params {
+    pub fn new(k: usize) -> Params {
+        assert!(
+            (1..=MAX_SOURCE_SYMBOLS).contains(&k),
+            "RaptorQ supports 1 to {} source symbols, got {}",
+            MAX_SOURCE_SYMBOLS,
+            k
+        );
+        let &(k_prime, j, s, h, w) = SYSTEMATIC_INDICES
+            .iter()
+            .find(|row| row.0 as usize >= k)
+            .unwrap();
+        let (k_prime, s, h, w) = (k_prime as usize, s as usize, h as usize, w as usize);
+        let l = k_prime + s + h;
+        let p = l - w;
+        let mut p1 = p;
+        while !is_prime(p1) {
+            p1 += 1;
+        }
+        Params {
+            k,
+            k_prime,
+            j,
+            s,
+            h,
+            w,
+            l,
+            p,
+            p1,
+        }
+    }
+
+    /// Internal symbol identifier of an encoding symbol, see section 5.3.1.
+    /// `None` if the ISI of a repair symbol does not fit into 32 bits.
+    pub fn isi(&self, esi: u32) -> Option<u32> {
+        if (esi as usize) < self.k {
+            Some(esi)
+        } else {
+            esi.checked_add((self.k_prime - self.k) as u32)
+        }
+    }
+
+    /// Indices of the intermediate symbols that are summed up to get
+    /// the encoding symbol with the given ISI, see sections 5.3.5.3 and 5.3.5.4
+    pub fn neighbours(&self, isi: u32) -> Vec<usize> {
+        let (w, p, p1) = (self.w as u32, self.p as u32, self.p1 as u32);
+
+        let mut a = 53591 + self.j * 997;
+        if a.is_multiple_of(2) {
+            a += 1;
+        }
+        let b = 10267 * (self.j + 1);
+        let y = (u64::from(b) + u64::from(isi) * u64::from(a)) as u32;
+        let v = rand(y, 0, 1 << 20);
+        let d = deg(v, w);
+        let a = 1 + rand(y, 1, w - 1);
+        let mut b = rand(y, 2, w);
+        let d1 = if d < 4 { 2 + rand(isi, 3, 2) } else { 2 };
+        let a1 = 1 + rand(isi, 4, p1 - 1);
+        let mut b1 = rand(isi, 5, p1);
+
+        let mut indices = Vec::with_capacity((d + d1) as usize);
+        indices.push(b as usize);
+        for _ in 1..d {
+            b = (b + a) % w;
+            indices.push(b as usize);
+        }
+        while b1 >= p {
+            b1 = (b1 + a1) % p1;
+        }
+        indices.push((w + b1) as usize);
+        for _ in 1..d1 {
+            b1 = (b1 + a1) % p1;
+            while b1 >= p {
+                b1 = (b1 + a1) % p1;
+            }
+            indices.push((w + b1) as usize);
+        }
+        indices
+    }
+
+    /// Row of the constraint matrix describing the encoding symbol with the given ISI
+    pub fn lt_row(&self, isi: u32) -> Row {
+        sparse_row(self.neighbours(isi))
+    }
+
+    /// The S LDPC and H HDPC rows of the constraint matrix, see section 5.3.3.3
+    #[allow(clippy::needless_range_loop)]
+    pub fn precode_rows(&self) -> Vec<Row> {
+        let (s, h, w, p, l) = (self.s, self.h, self.w, self.p, self.l);
+        let b = w - s;
+        let mut ldpc = vec![Vec::new(); s];
+
+        // G_LDPC,1 and I_S
+        for col in 0..b {
+            let a = 1 + col / s;
+            let mut row = col % s;
+            for _ in 0..3 {
+                ldpc[row].push(col);
+                row = (row + a) % s;
+            }
+        }
+        for (i, cols) in ldpc.iter_mut().enumerate() {
+            cols.push(b + i);
+            // G_LDPC,2
+            cols.push(w + i % p);
+            cols.push(w + (i + 1) % p);
+        }
+        let mut rows: Vec<Row> = ldpc.into_iter().map(sparse_row).collect();
+
+        // G_HDPC = MT * GAMMA, computed column by column from the right,
+        // followed by I_H
+        let ks = self.k_prime + s;
+        let mut hdpc = vec![vec![0; l]; h];
+        for i in 0..h {
+            hdpc[i][ks - 1] = OCT_EXP[i];
+        }
+        for col in (0..ks - 1).rev() {
+            for i in 0..h {
+                hdpc[i][col] = oct_mul(OCT_EXP[1], hdpc[i][col + 1]);
+            }
+            let i1 = rand(col as u32 + 1, 6, h as u32) as usize;
+            let i2 = (i1 + rand(col as u32 + 1, 7, h as u32 - 1) as usize + 1) % h;
+            hdpc[i1][col] ^= 1;
+            hdpc[i2][col] ^= 1;
+        }
+        for i in 0..h {
+            hdpc[i][ks + i] = 1;
+        }
+        rows.extend(hdpc.into_iter().map(Row::Dense));
+        rows
+    }
+
+    /// Equations every set of intermediate symbols satisfies: the precode
+    /// relationships plus the padding symbols, which are known to be zero.
+    pub fn known_equations(&self, blocksize: usize) -> (Vec<Row>, Vec<Vec<u8>>) {
+        let mut rows = self.precode_rows();
+        for isi in self.k..self.k_prime {
+            rows.push(self.lt_row(isi as u32));
+        }
+        let symbols = vec![vec![0; blocksize]; rows.len()];
+        (rows, symbols)
+    }
+
+    /// Computes an encoding symbol from the intermediate symbols
+    pub fn encode(&self, intermediate: &[Vec<u8>], isi: u32) -> Vec<u8> {
+        let mut symbol = vec![0; intermediate[0].len()];
+        for idx in self.neighbours(isi) {
+            add_mul_assign(&mut symbol, &intermediate[idx], 1);
+        }
+        symbol
+    }
+}
+
+/// Row of the constraint matrix
+#[derive(Debug, Clone)]
+pub(crate) enum Row {
+    /// The columns holding a one, used for the LDPC and LT rows
+    Sparse(Vec<usize>),
+    /// A coefficient for every column, used for the few HDPC rows
+    Dense(Vec<u8>),
+}
+
+/// A sparse row with a one in the columns which appear an odd number of times
+fn sparse_row(mut cols: Vec<usize>) -> Row {
+    cols.sort_unstable();
+    let mut row: Vec<usize> = Vec::with_capacity(cols.len());
+    for col in cols {
+        if row.last() == Some(&col) {
+            row.pop();
+        } else {
+            row.push(col);
+        }
+    }
+    Row::Sparse(row)
+}
+
+fn sparse(row: &Row) -> &[usize] {
+    match row {
+        Row::Sparse(cols) => cols,
+        Row::Dense(_) => unreachable!("dense rows are never peeled"),
+    }
+}
+
+/// What a column of the constraint matrix turned into during the elimination
+#[derive(Debug, Clone, Copy)]
+enum Col {
+    /// Solved by the n-th pivot
+    Pivot(usize),
+    /// The n-th inactive column
+    Inactive(usize),
+}
+
+/// A row operation on the symbols
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    /// `symbols[row] /= divisor`
+    Div { row: usize, divisor: u8 },
+    /// `symbols[dst] += factor * symbols[src]`
+    AddMul { dst: usize, src: usize, factor: u8 },
+}
+
+/// The row operations which solve a constraint matrix of full rank, found by
+/// `plan` without touching the symbols.
+pub(crate) struct Plan {
+    l: usize,
+    /// pivot row and column, in the order of peeling
+    pivots: Vec<(usize, usize)>,
+    cols: Vec<Col>,
+    /// per pivot, the inactive columns added to its row's symbol, as a bitset
+    inactive_sums: Vec<Vec<u64>>,
+    /// the rows which are not pivots, they form the dense system
+    remaining: Vec<usize>,
+    /// the elimination of the dense system
+    ops: Vec<Op>,
+    /// per inactive column, the row which ends up holding its symbol
+    solved: Vec<usize>,
+}
+
+/// Finds the row operations which solve `rows * C = symbols` for the `l`
+/// intermediate symbols `C`.
+///
+/// This is inactivation decoding in the spirit of section 5.4.2. Sparse rows
+/// are peeled off one column at a time, and columns which block peeling are
+/// inactivated. A pivot row then only holds its pivot column, earlier pivot
+/// columns and inactive columns, so forward substitution expresses each pivot
+/// column as its row's symbol plus a sum of inactive columns. The remaining
+/// rows, among them the dense HDPC rows, become a small dense system in the
+/// inactive columns, solved by Gaussian elimination. Time and memory grow with
+/// the number of nonzero entries and the square of the inactive columns.
+///
+/// Fails with the rank deficit of `rows`: no plan can succeed before as many
+/// more rows are added.
+pub(crate) fn plan(rows: &[Row], l: usize) -> Result<Plan, usize> {
+    let m = rows.len();
+    if m < l {
+        return Err(l - m);
+    }
+
+    // phase 1: peeling with inactivation. Elimination never creates entries in
+    // active columns, so counting the active columns of each row is enough.
+    let mut col_rows: Vec<Vec<usize>> = vec![Vec::new(); l];
+    let mut cnt_active = vec![0; m];
+    let mut queue = BinaryHeap::new();
+    for (i, row) in rows.iter().enumerate() {
+        if let Row::Sparse(cols) = row {
+            for &col in cols {
+                col_rows[col].push(i);
+            }
+            cnt_active[i] = cols.len();
+            queue.push(Reverse((cols.len(), i)));
+        }
+    }
+    let mut active = vec![true; l];
+    let mut used = vec![false; m];
+    let mut pivots: Vec<(usize, usize)> = Vec::new();
+    while let Some(Reverse((cnt, row))) = queue.pop() {
+        // the row was queued again with a lower count meanwhile
+        if used[row] || cnt == 0 || cnt != cnt_active[row] {
+            continue;
+        }
+        used[row] = true;
+        let cols: Vec<usize> = sparse(&rows[row])
+            .iter()
+            .copied()
+            .filter(|&col| active[col])
+            .collect();
+        // the first column becomes the pivot, the others are inactivated
+        for &col in &cols {
+            active[col] = false;
+            for &i in &col_rows[col] {
+                cnt_active[i] -= 1;
+                if !used[i] && cnt_active[i] > 0 {
+                    queue.push(Reverse((cnt_active[i], i)));
+                }
+            }
+        }
+        pivots.push((row, cols[0]));
+    }
+
+    let mut kinds = vec![None; l];
+    for (t, &(_, col)) in pivots.iter().enumerate() {
+        kinds[col] = Some(Col::Pivot(t));
+    }
+    let mut cnt_inactive = 0;
+    let cols: Vec<Col> = kinds
+        .into_iter()
+        .map(|kind| {
+            kind.unwrap_or_else(|| {
+                cnt_inactive += 1;
+                Col::Inactive(cnt_inactive - 1)
+            })
+        })
+        .collect();
+
+    // phase 2: forward substitution in the order of peeling, a pivot row only
+    // refers to the pivot columns before its own
+    let words = cnt_inactive.div_ceil(64);
+    let mut inactive_sums: Vec<Vec<u64>> = Vec::with_capacity(pivots.len());
+    for &(row, col) in &pivots {
+        let mut sum = vec![0; words];
+        for &c in sparse(&rows[row]) {
+            match cols[c] {
+                Col::Inactive(j) => sum[j / 64] ^= 1 << (j % 64),
+                Col::Pivot(t) if c != col => xor_words(&mut sum, &inactive_sums[t]),
+                Col::Pivot(_) => {}
+            }
+        }
+        inactive_sums.push(sum);
+    }
+
+    // phase 3: the remaining rows with the pivot columns substituted
+    let remaining: Vec<usize> = (0..m).filter(|&i| !used[i]).collect();
+    let mut dense: Vec<Vec<u8>> = remaining
+        .iter()
+        .map(|&i| substitute(&rows[i], &cols, &inactive_sums, cnt_inactive))
+        .collect();
+
+    // phase 4: Gauss-Jordan elimination of the dense system
+    let mut ops = Vec::new();
+    let mut solved = Vec::with_capacity(cnt_inactive);
+    let mut free: Vec<usize> = (0..remaining.len()).collect();
+    let mut pivoted: Vec<usize> = Vec::with_capacity(cnt_inactive);
+    let mut deficit = 0;
+    for j in 0..cnt_inactive {
+        let pos = match free.iter().position(|&r| dense[r][j] != 0) {
+            Some(pos) => pos,
+            None => {
+                // keep going to learn the whole deficit
+                deficit += 1;
+                continue;
+            }
+        };
+        let r = free.swap_remove(pos);
+        let pivot_val = dense[r][j];
+        if pivot_val != 1 {
+            div_assign(&mut dense[r][j..], pivot_val);
+            ops.push(Op::Div {
+                row: remaining[r],
+                divisor: pivot_val,
+            });
+        }
+        let pivot_row = dense[r][j..].to_vec();
+        for &i in free.iter().chain(pivoted.iter()) {
+            let factor = dense[i][j];
+            if factor != 0 {
+                add_mul_assign(&mut dense[i][j..], &pivot_row, factor);
+                ops.push(Op::AddMul {
+                    dst: remaining[i],
+                    src: remaining[r],
+                    factor,
+                });
+            }
+        }
+        pivoted.push(r);
+        solved.push(remaining[r]);
+    }
+    if deficit > 0 {
+        return Err(deficit);
+    }
+
+    Ok(Plan {
+        l,
+        pivots,
+        cols,
+        inactive_sums,
+        remaining,
+        ops,
+        solved,
+    })
+}
+
+impl Plan {
+    /// Solves for the intermediate symbols, given the symbols of the rows
+    /// passed to `plan`
+    pub fn apply(&self, rows: &[Row], mut symbols: Vec<Vec<u8>>) -> Vec<Vec<u8>> {
+        // phase 2
+        for &(row, col) in &self.pivots {
+            for &c in sparse(&rows[row]) {
+                match self.cols[c] {
+                    Col::Pivot(t) if c != col => add_row(&mut symbols, row, self.pivots[t].0, 1),
+                    _ => {}
+                }
+            }
+        }
+
+        // phase 3
+        for &i in &self.remaining {
+            match &rows[i] {
+                Row::Sparse(cols) => {
+                    for &c in cols {
+                        if let Col::Pivot(t) = self.cols[c] {
+                            add_row(&mut symbols, i, self.pivots[t].0, 1);
+                        }
+                    }
+                }
+                Row::Dense(factors) => {
+                    for (c, &factor) in factors.iter().enumerate() {
+                        if let (Col::Pivot(t), true) = (self.cols[c], factor != 0) {
+                            add_row(&mut symbols, i, self.pivots[t].0, factor);
+                        }
+                    }
+                }
+            }
+        }
+
+        // phase 4
+        for op in &self.ops {
+            match *op {
+                Op::Div { row, divisor } => div_assign(&mut symbols[row], divisor),
+                Op::AddMul { dst, src, factor } => add_row(&mut symbols, dst, src, factor),
+            }
+        }
+
+        // phase 5: back substitution of the pivot columns
+        let mut result = vec![Vec::new(); self.l];
+        for (t, &(row, col)) in self.pivots.iter().enumerate() {
+            let mut symbol = std::mem::take(&mut symbols[row]);
+            for j in ones(&self.inactive_sums[t]) {
+                xor_into(&mut symbol, &symbols[self.solved[j]]);
+            }
+            result[col] = symbol;
+        }
+        for (col, kind) in self.cols.iter().enumerate() {
+            if let Col::Inactive(j) = *kind {
+                result[col] = std::mem::take(&mut symbols[self.solved[j]]);
+            }
+        }
+        result
+    }
+}
+
+/// Solves `rows * C = symbols` for the `l` intermediate symbols `C`, see `plan`
+pub(crate) fn solve(rows: &[Row], symbols: Vec<Vec<u8>>, l: usize) -> Result<Vec<Vec<u8>>, usize> {
+    Ok(plan(rows, l)?.apply(rows, symbols))
+}
+
+/// The coefficients of a remaining row in the inactive columns, once the
+/// pivot columns are replaced by their sums of inactive columns
+fn substitute(row: &Row, cols: &[Col], inactive_sums: &[Vec<u64>], cnt_inactive: usize) -> Vec<u8> {
+    let words = cnt_inactive.div_ceil(64);
+    let mut coefs = vec![0; cnt_inactive];
+    // the sums are binary, so they are collected per factor first
+    let mut sums: Vec<Vec<u64>> = vec![Vec::new(); 256];
+    let mut add = |col: usize, factor: u8| match cols[col] {
+        Col::Inactive(j) => coefs[j] ^= factor,
+        Col::Pivot(t) => {
+            let sum = &mut sums[factor as usize];
+            sum.resize(words, 0);
+            xor_words(sum, &inactive_sums[t]);
+        }
+    };
+    match row {
+        Row::Sparse(cols) => cols.iter().for_each(|&col| add(col, 1)),
+        Row::Dense(factors) => {
+            for (col, &factor) in factors.iter().enumerate() {
+                if factor != 0 {
+                    add(col, factor);
+                }
+            }
+        }
+    }
+    for (factor, sum) in sums.iter().enumerate() {
+        for j in ones(sum) {
+            coefs[j] ^= factor as u8;
+        }
+    }
+    coefs
+}
+
+fn xor_words(dst: &mut [u64], src: &[u64]) {
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= s;
+    }
+}
+
+/// The positions of the set bits
+fn ones(bits: &[u64]) -> impl Iterator<Item = usize> + '_ {
+    bits.iter().enumerate().flat_map(|(i, &word)| {
+        let mut word = word;
+        std::iter::from_fn(move || {
+            if word == 0 {
+                return None;
+            }
+            let bit = word.trailing_zeros() as usize;
+            word &= word - 1;
+            Some(i * 64 + bit)
+        })
+    })
+}
+
+/// `symbols[dst] += factor * symbols[src]`
+fn add_row(symbols: &mut [Vec<u8>], dst: usize, src: usize, factor: u8) {
+    if dst < src {
+        let (head, tail) = symbols.split_at_mut(src);
+        add_mul_assign(&mut head[dst], &tail[0], factor);
+    } else {
+        let (head, tail) = symbols.split_at_mut(dst);
+        add_mul_assign(&mut tail[0], &head[src], factor);
+    }
+}
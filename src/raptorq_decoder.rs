@@ -0,0 +1,170 @@
+use crate::{
+    decoder::{DecodeError, Statistics},
+    droplet::Droplet,
+    raptorq::{plan, Params, Row},
+    types::{CatchResult, DropType},
+};
+use std::collections::HashSet;
+
+/// Decoder for RaptorQ codes as specified in RFC 6330
+pub struct RaptorQDecoder {
+    total_length: usize,
+    blocksize: usize,
+    params: Params,
+    cnt_received_drops: usize,
+    esis: HashSet<u32>,
+    /// the known equations followed by one row per received symbol
+    rows: Vec<Row>,
+    symbols: Vec<Vec<u8>>,
+    /// the rank deficit left by the last attempt, every symbol lowers it by at most one
+    cnt_needed: usize,
+    source: Vec<Option<Vec<u8>>>,
+    unknown_chunks: usize,
+    cnt_received_source: usize,
+}
+
+impl RaptorQDecoder {
+    /// Creates a new Decoder for RaptorQ codes
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate fountaincode;
+    ///
+    /// fn main() {
+    ///     use self::fountaincode::raptorq_encoder::RaptorQEncoder;
+    ///     use self::fountaincode::raptorq_decoder::RaptorQDecoder;
+    ///     use self::fountaincode::encoder::Encoder;
+    ///     use self::fountaincode::types::*;
+    ///     use self::rand::{thread_rng, Rng};
+    ///     use rand::distributions::Alphanumeric;
+    ///
+    ///     let s:String = thread_rng().sample_iter(Alphanumeric).take(1024).collect();
+    ///     let buf = s.into_bytes();
+    ///     let to_compare = buf.clone();
+    ///     let length = buf.len();
+    ///
+    ///     let mut enc = RaptorQEncoder::new(buf, 64, EncoderType::Random);
+    ///     let mut dec = RaptorQDecoder::new(length, 64);
+    ///
+    ///     loop {
+    ///         let drop = enc.next();
+    ///         match dec.catch(drop).unwrap() {
+    ///             CatchResult::Missing(stats) => {
+    ///                 println!("Missing blocks {:?}", stats);
+    ///             }
+    ///             CatchResult::Finished(data, stats) => {
+    ///                 assert_eq!(to_compare, data);
+    ///                 println!("Finished, stats: {:?}", stats);
+    ///                 return
+    ///             }
+    ///             CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object is empty or has more than `raptorq::MAX_SOURCE_SYMBOLS`
+    /// blocks.
+    pub fn new(len: usize, blocksize: usize) -> RaptorQDecoder {
+        let params = Params::new(len.div_ceil(blocksize));
+        let (rows, symbols) = params.known_equations(blocksize);
+        RaptorQDecoder {
+            total_length: len,
+            blocksize,
+            cnt_received_drops: 0,
+            esis: HashSet::new(),
+            rows,
+            symbols,
+            cnt_needed: params.k,
+            source: vec![None; params.k],
+            unknown_chunks: params.k,
+            cnt_received_source: 0,
+            params,
+        }
+    }
+
+    /// Solves for the intermediate symbols and recovers the missing source symbols.
+    /// The symbols are only touched once the equations have full rank.
+    fn try_decode(&mut self) {
+        match plan(&self.rows, self.params.l) {
+            Ok(plan) => {
+                let symbols = std::mem::take(&mut self.symbols);
+                let intermediate = plan.apply(&self.rows, symbols);
+                for (isi, symbol) in self.source.iter_mut().enumerate() {
+                    if symbol.is_none() {
+                        *symbol = Some(self.params.encode(&intermediate, isi as u32));
+                    }
+                }
+                self.unknown_chunks = 0;
+                self.rows = Vec::new();
+            }
+            Err(deficit) => self.cnt_needed = deficit,
+        }
+    }
+
+    /// Catches a Droplet
+    /// When it is possible to reconstruct a set, the bytes are returned.
+    ///
+    /// Droplets which were not produced by a `RaptorQEncoder` for this object
+    /// are rejected and not counted.
+    pub fn catch(&mut self, drop: Droplet) -> Result<CatchResult, DecodeError> {
+        if self.unknown_chunks == 0 {
+            return Err(DecodeError::AlreadyFinished);
+        }
+        if drop.data.len() != self.blocksize {
+            return Err(DecodeError::PayloadLength {
+                expected: self.blocksize,
+                actual: drop.data.len(),
+            });
+        }
+        let esi = match drop.droptype {
+            DropType::Esi(esi) => esi,
+            _ => return Err(DecodeError::ParameterMismatch),
+        };
+        let isi = self
+            .params
+            .isi(esi)
+            .ok_or(DecodeError::EsiOutOfRange { esi })?;
+        self.cnt_received_drops += 1;
+
+        if self.esis.insert(esi) {
+            if (esi as usize) < self.params.k {
+                self.source[esi as usize] = Some(drop.data.clone());
+                self.unknown_chunks -= 1;
+                self.cnt_received_source += 1;
+            }
+            self.rows.push(self.params.lt_row(isi));
+            self.symbols.push(drop.data);
+            self.cnt_needed -= 1;
+            if self.unknown_chunks > 0 && self.cnt_needed == 0 {
+                self.try_decode();
+            }
+        }
+
+        let stats = Statistics {
+            cnt_droplets: self.cnt_received_drops,
+            cnt_chunks: self.params.k,
+            overhead: self.cnt_received_drops as f32 * 100.0 / self.params.k as f32,
+            unknown_chunks: self.unknown_chunks,
+            cnt_peeled: self.cnt_received_source,
+            cnt_eliminated: self.params.k - self.unknown_chunks - self.cnt_received_source,
+            cnt_corrupt: 0,
+            cnt_unauthenticated: 0,
+        };
+
+        if self.unknown_chunks == 0 {
+            let mut result = Vec::with_capacity(self.params.k * self.blocksize);
+            for symbol in &self.source {
+                result.extend_from_slice(symbol.as_ref().unwrap());
+            }
+            result.truncate(self.total_length);
+            Ok(CatchResult::Finished(result, stats))
+        } else {
+            Ok(CatchResult::Missing(stats))
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::{
+    droplet::Droplet,
+    encoder::Encoder,
+    raptorq::{solve, Params},
+    types::{DropType, EncoderType},
+};
+use std::{cmp, convert::TryFrom};
+
+/// Encoder for RaptorQ codes as specified in RFC 6330
+#[derive(Clone)]
+pub struct RaptorQEncoder {
+    data: Vec<u8>,
+    blocksize: usize,
+    params: Params,
+    intermediate: Vec<Vec<u8>>,
+    pub cnt: usize,
+}
+
+impl RaptorQEncoder {
+    /// Constructs a new encoder for RaptorQ codes.
+    /// The blocksize is the symbol size T of RFC 6330, the whole object forms a
+    /// single source block of at most `raptorq::MAX_SOURCE_SYMBOLS` symbols.
+    ///
+    /// RaptorQ is a systematic code. The Systematic encoder first produces the source
+    /// symbols and continues with repair symbols, the Random encoder only produces
+    /// repair symbols.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the object is empty or has more than `raptorq::MAX_SOURCE_SYMBOLS`
+    /// blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate fountaincode;
+    ///
+    /// fn main() {
+    ///     use fountaincode::raptorq_encoder::RaptorQEncoder;
+    ///     use fountaincode::encoder::Encoder;
+    ///     use fountaincode::types::EncoderType;
+    ///     use self::rand::{thread_rng, Rng};
+    ///     use rand::distributions::Alphanumeric;
+    ///
+    ///     let s: String = thread_rng().sample_iter(Alphanumeric).take(1024).collect();
+    ///     let buf = s.into_bytes();
+    ///
+    ///     let mut enc = RaptorQEncoder::new(buf, 64, EncoderType::Systematic);
+    ///
+    ///     for i in 1..10 {
+    ///         println!("droplet {:?}: {:?}", i, enc.next());
+    ///     }
+    /// }
+    /// ```
+    pub fn new(data: Vec<u8>, blocksize: usize, encodertype: EncoderType) -> RaptorQEncoder {
+        let len = data.len();
+        let cnt_blocks = len.div_ceil(blocksize);
+        let params = Params::new(cnt_blocks);
+
+        let (mut rows, mut symbols) = params.known_equations(blocksize);
+        for isi in 0..params.k {
+            rows.push(params.lt_row(isi as u32));
+            symbols.push(source_symbol(&data, isi, blocksize));
+        }
+        let intermediate = solve(&rows, symbols, params.l)
+            .expect("the RaptorQ constraint matrix is always invertible");
+
+        let cnt = match encodertype {
+            EncoderType::Systematic => 0,
+            EncoderType::Random => params.k,
+        };
+        RaptorQEncoder {
+            data,
+            blocksize,
+            params,
+            intermediate,
+            cnt,
+        }
+    }
+}
+
+fn source_symbol(data: &[u8], idx: usize, blocksize: usize) -> Vec<u8> {
+    let begin = idx * blocksize;
+    let end = cmp::min((idx + 1) * blocksize, data.len());
+    let mut symbol = vec![0; blocksize];
+    symbol[..end - begin].copy_from_slice(&data[begin..end]);
+    symbol
+}
+
+impl Encoder for RaptorQEncoder {
+    fn next(&mut self) -> Droplet {
+        let esi = u32::try_from(self.cnt).expect("the encoding symbol ids are exhausted");
+        let data = if self.cnt < self.params.k {
+            source_symbol(&self.data, self.cnt, self.blocksize)
+        } else {
+            let isi = self
+                .params
+                .isi(esi)
+                .expect("the encoding symbol ids are exhausted");
+            self.params.encode(&self.intermediate, isi)
+        };
+        self.cnt += 1;
+        Droplet::new(DropType::Esi(esi), data)
+    }
+}
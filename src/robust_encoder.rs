@@ -1,30 +1,14 @@
 use crate::{
-    droplet::Droplet,
-    encoder::Encoder,
+    lt_encoder::LtEncoder,
     robust_soliton::RobustSoliton,
-    soliton::Soliton,
-    types::{DropType, EncoderType},
+    storage::{BlockSource, SeekSource},
+    types::EncoderType,
 };
-use rand::{
-    distributions::Uniform,
-    rngs::StdRng,
-    {Rng, SeedableRng},
-};
-use std::{cmp, vec::Vec};
+use std::io::{self, Read, Seek};
 
-/// Encoder for Luby transform codes
-#[derive(Clone)]
-pub struct RobustEncoder {
-    data: Vec<u8>,
-    len: usize,
-    blocksize: usize,
-    rng: StdRng,
-    dist: rand::distributions::Uniform<usize>,
-    cnt_blocks: usize,
-    sol: RobustSoliton,
-    pub cnt: usize,
-    encodertype: EncoderType,
-}
+/// Encoder for Luby transform codes with the robust soliton distribution,
+/// an `LtEncoder` with inherent constructors for it.
+pub type RobustEncoder<B = Vec<u8>> = LtEncoder<RobustSoliton, B>;
 
 impl RobustEncoder {
     /// Constructs a new encoder for Luby transform codes.
@@ -69,73 +53,74 @@ impl RobustEncoder {
         spike: Option<usize>,
         delta: f32,
     ) -> RobustEncoder {
-        let mut rng = StdRng::from_entropy();
+        RobustEncoder::with_seed(
+            data,
+            blocksize,
+            encodertype,
+            c,
+            spike,
+            delta,
+            rand::random(),
+        )
+    }
 
+    /// Constructs a new encoder whose droplet stream is determined by `seed`
+    pub fn with_seed(
+        data: Vec<u8>,
+        blocksize: usize,
+        encodertype: EncoderType,
+        c: f32,
+        spike: Option<usize>,
+        delta: f32,
+        seed: u64,
+    ) -> RobustEncoder {
         let len = data.len();
-        let cnt_blocks = ((len as f32) / blocksize as f32).ceil() as usize;
+        RobustEncoder::with_source(data, len, blocksize, encodertype, c, spike, delta, seed)
+    }
+}
 
-        let sol = RobustSoliton::new(cnt_blocks, rng.gen::<u64>(), c, spike, delta);
-        RobustEncoder {
-            data,
+impl<R: Read + Seek> RobustEncoder<SeekSource<R>> {
+    /// Constructs a new encoder which reads the source blocks from `reader` when they are needed
+    pub fn from_reader(
+        reader: R,
+        blocksize: usize,
+        encodertype: EncoderType,
+        c: f32,
+        spike: Option<usize>,
+        delta: f32,
+    ) -> io::Result<RobustEncoder<SeekSource<R>>> {
+        let (source, len) = SeekSource::new(reader)?;
+        Ok(RobustEncoder::with_source(
+            source,
             len,
             blocksize,
-            rng,
-            dist: Uniform::new(0, cnt_blocks),
-            cnt_blocks,
-            sol,
-            cnt: 0,
             encodertype,
-        }
+            c,
+            spike,
+            delta,
+            rand::random(),
+        ))
     }
 }
 
-pub fn get_sample_from_rng_by_seed(
-    seed: u64,
-    range: rand::distributions::Uniform<usize>,
-    degree: usize,
-) -> impl Iterator<Item = usize> {
-    let rng: StdRng = SeedableRng::seed_from_u64(seed);
-    rng.sample_iter(range).take(degree)
-}
-
-impl Encoder for RobustEncoder {
-    fn next(&mut self) -> Droplet {
-        let drop = match self.encodertype {
-            EncoderType::Random => {
-                let degree = self.sol.next();
-                let seed = self.rng.gen::<u64>();
-                let sample = get_sample_from_rng_by_seed(seed, self.dist, degree);
-                let mut r = vec![0; self.blocksize];
-
-                for k in sample {
-                    let begin = k * self.blocksize;
-                    let end = cmp::min((k + 1) * self.blocksize, self.len);
-
-                    for (src_dat, drop_dat) in self.data[begin..end].iter().zip(r.iter_mut()) {
-                        *drop_dat ^= src_dat;
-                    }
-                }
-                Droplet::new(DropType::Seeded(seed, degree), r)
-            }
-            EncoderType::Systematic => {
-                let begin = (self.cnt % self.cnt_blocks) * self.blocksize;
-                let end = cmp::min(
-                    ((self.cnt % self.cnt_blocks) + 1) * self.blocksize,
-                    self.len,
-                );
-                let mut r = vec![0; self.blocksize];
-
-                for (src_dat, drop_dat) in self.data[begin..end].iter().zip(r.iter_mut()) {
-                    *drop_dat = *src_dat;
-                }
-                if (self.cnt + 2) > self.cnt_blocks * 2 {
-                    self.encodertype = EncoderType::Random;
-                }
-                Droplet::new(DropType::Edges(self.cnt % self.cnt_blocks), r)
-            }
-        };
-
-        self.cnt += 1;
-        drop
+impl<B: BlockSource> RobustEncoder<B> {
+    /// Constructs a new encoder over a `BlockSource` which holds an object of `len` bytes.
+    /// The droplets are the same as the ones of an in-memory encoder with the same seed.
+    #[allow(clippy::too_many_arguments)]
+    pub fn with_source(
+        source: B,
+        len: usize,
+        blocksize: usize,
+        encodertype: EncoderType,
+        c: f32,
+        spike: Option<usize>,
+        delta: f32,
+        seed: u64,
+    ) -> RobustEncoder<B> {
+        LtEncoder::builder(blocksize)
+            .encodertype(encodertype)
+            .seed(seed)
+            .distribution(|k, seed| RobustSoliton::new(k, seed, c, spike, delta))
+            .build_with_source(source, len)
     }
 }
@@ -4,101 +4,110 @@ use rand::{rngs::StdRng, Rng, SeedableRng};
 #[derive(Debug, Clone)]
 pub struct RobustSoliton {
     k: usize,
-    // constant
-    c: f32,
     // another constant
-    r: f32,
+    r: f64,
     // failure probability
-    delta: f32,
+    delta: f64,
     // normalization factor
-    beta: f32,
+    beta: f64,
     // spike position, usually: k/R unless configured manually as a tuning parameter
     m: usize,
+    // cdf[d - 1] is the probability of a degree <= d
+    cdf: Vec<f64>,
     rng: StdRng,
-    curr: u64,
 }
 
 impl RobustSoliton {
     pub fn new(k: usize, seed: u64, c: f32, spike: Option<usize>, delta: f32) -> RobustSoliton {
-        let r = compute_r(k, c, delta);
-        if let Some(m) = spike {
+        let k = k.max(1);
+        let delta = f64::from(delta);
+        let (m, r) = match spike {
             // Spike position was given, use that instead of calculating
-            let r = k as f32 / m as f32;
-            let beta = compute_beta(k, m, r, delta);
-            RobustSoliton {
-                k,
-                c,
-                r,
-                delta,
-                beta,
-                m,
-                curr: 0,
-                rng: SeedableRng::seed_from_u64(seed),
+            Some(m) => {
+                let m = m.max(1).min(k);
+                (m, k as f64 / m as f64)
             }
-        } else {
-            let m = compute_m(k, r);
-            let beta = compute_beta(k, m, r, delta);
-            RobustSoliton {
-                k,
-                c,
-                r,
-                delta,
-                beta,
-                m,
-                curr: 0,
-                rng: SeedableRng::seed_from_u64(seed),
+            None => {
+                let r = compute_r(k, f64::from(c), delta);
+                (compute_m(k, r), r)
             }
+        };
+        let beta = compute_beta(k, m, r, delta);
+
+        let mut cdf = Vec::with_capacity(k);
+        let mut sum = 0.0;
+        for d in 1..=k {
+            sum += (rho(k, d) + tau(d, m, r, delta)) / beta;
+            cdf.push(sum);
+        }
+
+        RobustSoliton {
+            k,
+            r,
+            delta,
+            beta,
+            m,
+            cdf,
+            rng: SeedableRng::seed_from_u64(seed),
+        }
+    }
+
+    /// The probability of degree `d`
+    pub fn pmf(&self, d: usize) -> f64 {
+        if d == 0 || d > self.k {
+            0.0
+        } else {
+            (rho(self.k, d) + tau(d, self.m, self.r, self.delta)) / self.beta
         }
     }
+
+    /// The position of the spike
+    pub fn spike(&self) -> usize {
+        self.m
+    }
 }
 
 impl Soliton for RobustSoliton {
     fn next(&mut self) -> usize {
-        let mut sum = 0.0;
-        let mut index = 1;
-        let u = self.rng.gen::<f32>();
+        let u = self.rng.gen::<f64>();
+        self.degree(u)
+    }
 
-        while sum <= u {
-            sum +=
-                (rho(self.k, index) + tau(index as usize, self.m, self.r, self.delta)) / self.beta;
-            index += 1;
-        }
-        self.curr += 1;
-        index - 1
+    fn degree(&self, u: f64) -> usize {
+        // the last entry may be slightly below one, rounding errors end in degree k
+        let idx = self.cdf.partition_point(|p| *p <= u);
+        idx.min(self.k - 1) + 1
     }
 }
 
-fn compute_r(k: usize, c: f32, delta: f32) -> f32 {
-    c * ((k as f32) / delta).ln() * (k as f32).sqrt()
+fn compute_r(k: usize, c: f64, delta: f64) -> f64 {
+    c * ((k as f64) / delta).ln() * (k as f64).sqrt()
 }
 
-fn compute_m(k: usize, r: f32) -> usize {
-    ((k as f32) / r).floor() as usize
+fn compute_m(k: usize, r: f64) -> usize {
+    // for small k, R exceeds k and the spike would be at degree 0
+    (((k as f64) / r).floor() as usize).max(1).min(k)
 }
 
-fn compute_beta(k: usize, m: usize, r: f32, delta: f32) -> f32 {
-    let mut sum = 0.0;
-
-    for pos in 1..k {
-        sum += rho(k, pos) + tau(pos, m, r, delta)
-    }
-    sum
+fn compute_beta(k: usize, m: usize, r: f64, delta: f64) -> f64 {
+    (1..=k).map(|pos| rho(k, pos) + tau(pos, m, r, delta)).sum()
 }
 
-fn tau(index: usize, m: usize, r: f32, delta: f32) -> f32 {
+fn tau(index: usize, m: usize, r: f64, delta: f64) -> f64 {
     if index >= 1 && index < m {
-        (1 / (index * m)) as f32
+        1.0 / (index * m) as f64
     } else if index == m {
-        (r / delta).ln() as f32 / m as f32
+        // negative when R < delta, which only happens for tiny k
+        ((r / delta).ln() / m as f64).max(0.0)
     } else {
         0.0
     }
 }
 
-fn rho(k: usize, i: usize) -> f32 {
+fn rho(k: usize, i: usize) -> f64 {
     if i == 1 {
-        1.0 / k as f32
+        1.0 / k as f64
     } else {
-        1.0 / (i * (i - 1)) as f32
+        1.0 / (i * (i - 1)) as f64
     }
 }
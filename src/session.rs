@@ -0,0 +1,208 @@
+use crate::{
+    auth::AuthKey,
+    decoder::Decoder,
+    droplet::Droplet,
+    types::{CatchResult, DecoderMode, DropType},
+    wire::{Limits, ObjectParams, WireError},
+};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    time::{Duration, Instant},
+};
+
+struct Transfer {
+    params: ObjectParams,
+    decoder: Decoder,
+    last_seen: Instant,
+}
+
+/// Decodes many objects at once, e.g. all transfers arriving at one socket.
+///
+/// Droplets are routed by the object id of their `ObjectParams`. A decoder is
+/// created for the first droplet of an object and dropped once the object is
+/// complete. Late droplets of a completed object are ignored until it is evicted.
+/// Objects beyond the `Limits`, by default 1 GiB, are not decoded, and the
+/// droplets of new objects are ignored while the objects being decoded hold
+/// `Limits::max_total_size` bytes.
+pub struct Session {
+    transfers: HashMap<u64, Transfer>,
+    /// the sum of the sizes of `transfers`
+    size_active: usize,
+    completed: HashMap<u64, Instant>,
+    max_idle: Duration,
+    mode: DecoderMode,
+    auth_key: Option<AuthKey>,
+    limits: Limits,
+    cnt_unauthenticated: usize,
+}
+
+impl Session {
+    /// Creates a session which forgets objects after `max_idle` without a droplet
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// extern crate rand;
+    /// extern crate fountaincode;
+    ///
+    /// fn main() {
+    ///     use self::fountaincode::ideal_encoder::IdealEncoder;
+    ///     use self::fountaincode::encoder::Encoder;
+    ///     use self::fountaincode::session::Session;
+    ///     use self::fountaincode::types::*;
+    ///     use self::fountaincode::wire::ObjectParams;
+    ///     use std::time::Duration;
+    ///
+    ///     let objects = vec![vec![1; 1000], vec![2; 3000]];
+    ///     let mut senders: Vec<_> = objects
+    ///         .iter()
+    ///         .enumerate()
+    ///         .map(|(id, data)| {
+    ///             let params = ObjectParams::new(data.len(), 100).with_object_id(id as u64);
+    ///             (params, IdealEncoder::new(data.clone(), 100, EncoderType::Random))
+    ///         })
+    ///         .collect();
+    ///
+    ///     let mut session = Session::new(Duration::from_secs(10));
+    ///     let mut done = 0;
+    ///     while done < objects.len() {
+    ///         for (params, enc) in senders.iter_mut() {
+    ///             let packet = enc.next().to_bytes(params);
+    ///             if let Ok(Some((id, CatchResult::Finished(data, _)))) = session.receive(&packet) {
+    ///                 assert_eq!(objects[id as usize], data);
+    ///                 done += 1;
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn new(max_idle: Duration) -> Session {
+        Session::with_mode(max_idle, DecoderMode::Peeling)
+    }
+
+    /// Creates a session whose decoders decode with the given mode
+    pub fn with_mode(max_idle: Duration, mode: DecoderMode) -> Session {
+        Session {
+            transfers: HashMap::new(),
+            size_active: 0,
+            completed: HashMap::new(),
+            max_idle,
+            mode,
+            auth_key: None,
+            limits: Limits::default(),
+            cnt_unauthenticated: 0,
+        }
+    }
+
+    /// Only accepts packets authenticated with `key` in `receive`, the others
+    /// are dropped before they reach a decoder and counted.
+    pub fn set_auth_key(&mut self, key: AuthKey) {
+        self.auth_key = Some(key);
+    }
+
+    /// Only decodes objects within `limits`, the droplets of larger ones are
+    /// ignored. Each object being decoded is held in memory.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    /// Parses a packet and catches its droplet, see `catch`.
+    /// Returns the object id together with the result. Packets of objects
+    /// beyond the limits fail with `WireError::ObjectTooLarge`.
+    pub fn receive(&mut self, packet: &[u8]) -> Result<Option<(u64, CatchResult)>, WireError> {
+        let parsed = match self.auth_key {
+            Some(ref key) => Droplet::from_bytes_authenticated(packet, key),
+            None => Droplet::from_bytes(packet),
+        };
+        if let Err(WireError::Unauthenticated) = parsed {
+            self.cnt_unauthenticated += 1;
+        }
+        let (drop, params) = parsed?;
+        self.limits.check(&params)?;
+        Ok(self
+            .catch(params, drop)
+            .map(|result| (params.object_id, result)))
+    }
+
+    /// Catches a droplet of the object described by `params`.
+    ///
+    /// Returns `None` if the droplet is ignored: its object is already complete,
+    /// it does not match the parameters of the first droplet of its object,
+    /// the object is empty or exceeds the limits, it is a new object and the
+    /// objects being decoded exhaust `Limits::max_total_size`, the droplet is
+    /// a RaptorQ symbol, or the decoder rejects it.
+    pub fn catch(&mut self, params: ObjectParams, drop: Droplet) -> Option<CatchResult> {
+        let now = Instant::now();
+        if let DropType::Esi(_) = drop.droptype {
+            return None;
+        }
+        if params.len == 0
+            || self.limits.check(&params).is_err()
+            || drop.data.len() != params.blocksize
+            || self.completed.contains_key(&params.object_id)
+        {
+            return None;
+        }
+
+        let transfer = match self.transfers.entry(params.object_id) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                // every decoder holds its whole object
+                if self.size_active.saturating_add(params.len) > self.limits.max_total_size {
+                    return None;
+                }
+                self.size_active += params.len;
+                entry.insert(Transfer {
+                    params,
+                    decoder: Decoder::with_mode(params.len, params.blocksize, self.mode),
+                    last_seen: now,
+                })
+            }
+        };
+        if transfer.params != params {
+            return None;
+        }
+        transfer.last_seen = now;
+
+        let result = transfer.decoder.catch(drop).ok()?;
+        if let CatchResult::Finished(_, _) = result {
+            self.transfers.remove(&params.object_id);
+            self.size_active -= params.len;
+            self.completed.insert(params.object_id, now);
+        }
+        Some(result)
+    }
+
+    /// Drops the objects which did not receive a droplet for `max_idle`,
+    /// and forgets the objects completed longer ago. Returns the ids of the
+    /// incomplete objects which were dropped.
+    pub fn evict_stale(&mut self, now: Instant) -> Vec<u64> {
+        let max_idle = self.max_idle;
+        let is_stale = |last_seen: Instant| now.saturating_duration_since(last_seen) >= max_idle;
+
+        let stale: Vec<u64> = self
+            .transfers
+            .iter()
+            .filter(|(_, transfer)| is_stale(transfer.last_seen))
+            .map(|(id, _)| *id)
+            .collect();
+        for id in stale.iter() {
+            if let Some(transfer) = self.transfers.remove(id) {
+                self.size_active -= transfer.params.len;
+            }
+        }
+        self.completed
+            .retain(|_, completed_at| !is_stale(*completed_at));
+        stale
+    }
+
+    /// The number of objects which are being decoded
+    pub fn cnt_active(&self) -> usize {
+        self.transfers.len()
+    }
+
+    /// The number of packets dropped because they were not authenticated
+    pub fn cnt_unauthenticated(&self) -> usize {
+        self.cnt_unauthenticated
+    }
+}
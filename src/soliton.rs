@@ -1,3 +1,7 @@
 pub trait Soliton {
     fn next(&mut self) -> usize;
+
+    /// Maps a uniform sample `u` in [0, 1) to a degree, the inverse of the
+    /// cumulative distribution. Encoders use it to draw reproducible degrees.
+    fn degree(&self, u: f64) -> usize;
 }
@@ -0,0 +1,165 @@
+use crate::wire::block_len;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+
+/// Random access storage for the blocks of an object.
+///
+/// The decoder writes every block as soon as it is recovered, and reads
+/// recovered blocks back to reduce later droplets.
+pub trait BlockStore {
+    /// Writes `data` at byte `offset`
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()>;
+
+    /// Fills `buf` with the bytes at `offset`, which have been written before
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()>;
+
+    /// Called once the object is complete
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl BlockStore for Vec<u8> {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        let begin = offset as usize;
+        if self.len() < begin + data.len() {
+            self.resize(begin + data.len(), 0);
+        }
+        self[begin..begin + data.len()].copy_from_slice(data);
+        Ok(())
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.as_mut_slice().read_at(offset, buf)
+    }
+}
+
+/// A fixed size buffer, e.g. a memory mapped file
+impl BlockStore for &mut [u8] {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        let begin = offset as usize;
+        match self.get_mut(begin..begin + data.len()) {
+            Some(dst) => {
+                dst.copy_from_slice(data);
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::WriteZero,
+                "block does not fit into the buffer",
+            )),
+        }
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let begin = offset as usize;
+        match self.get(begin..begin + buf.len()) {
+            Some(src) => {
+                buf.copy_from_slice(src);
+                Ok(())
+            }
+            None => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+/// Stores the blocks in anything seekable, e.g. a `File`
+#[derive(Debug)]
+pub struct SeekStore<W> {
+    inner: W,
+}
+
+impl<W> SeekStore<W> {
+    pub fn new(inner: W) -> SeekStore<W> {
+        SeekStore { inner }
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+impl<W: Read + Write + Seek> BlockStore for SeekStore<W> {
+    fn write_at(&mut self, offset: u64, data: &[u8]) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(offset))?;
+        self.inner.write_all(data)
+    }
+
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(offset))?;
+        self.inner.read_exact(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Random access to the source blocks of an encoder
+pub trait BlockSource {
+    /// Fills `buf` with the bytes at `offset`
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()>;
+}
+
+impl BlockSource for &[u8] {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        let begin = offset as usize;
+        match self.get(begin..begin + buf.len()) {
+            Some(src) => {
+                buf.copy_from_slice(src);
+                Ok(())
+            }
+            None => Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+impl BlockSource for Vec<u8> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.as_slice().read_at(offset, buf)
+    }
+}
+
+/// Reads the source blocks from anything seekable, e.g. a `File`
+#[derive(Debug)]
+pub struct SeekSource<R> {
+    inner: R,
+}
+
+impl<R: Read + Seek> SeekSource<R> {
+    /// Returns the source and the length of the object
+    pub fn new(mut inner: R) -> io::Result<(SeekSource<R>, usize)> {
+        let len = inner.seek(SeekFrom::End(0))?;
+        Ok((SeekSource { inner }, len as usize))
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read + Seek> BlockSource for SeekSource<R> {
+    fn read_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.inner.seek(SeekFrom::Start(offset))?;
+        self.inner.read_exact(buf)
+    }
+}
+
+/// Reads block `idx` of an object of `len` bytes into `buf`. The last block
+/// is short if `len` is not a multiple of the block size, its padding is zero.
+pub(crate) fn read_block<B: BlockSource>(
+    source: &mut B,
+    idx: usize,
+    len: usize,
+    buf: &mut [u8],
+) -> io::Result<()> {
+    let blocksize = buf.len();
+    let (data, padding) = buf.split_at_mut(block_len(len, blocksize, idx));
+    source.read_at((idx * blocksize) as u64, data)?;
+    for b in padding.iter_mut() {
+        *b = 0;
+    }
+    Ok(())
+}
@@ -11,16 +11,29 @@ pub enum EncoderType {
     Random,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecoderMode {
+    /// Only belief propagation, a block is recovered once a droplet covers no other unknown block
+    Peeling,
+    /// Peeling, plus Gaussian elimination over the remaining droplets once peeling stalls.
+    /// Needs fewer droplets, but keeps a copy of every droplet until the end.
+    Elimination,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum DropType {
     /// First is seed, second degree
     Seeded(u64, usize),
     /// Just a list of edges
     Edges(usize),
+    /// Encoding symbol identifier of a RaptorQ symbol
+    Esi(u32),
 }
 
 #[derive(Debug)]
 pub enum CatchResult {
     Finished(Vec<u8>, Statistics),
     Missing(Statistics),
+    /// All blocks are recovered, but the object does not match the expected digest
+    DigestMismatch(Statistics),
 }
@@ -0,0 +1,490 @@
+//! Binary packet format for droplets.
+//!
+//! Every packet starts with a fixed size header, followed by the payload.
+//! All integers are big-endian.
+//!
+//! | offset | size | field                                          |
+//! |--------|------|------------------------------------------------|
+//! | 0      | 1    | format version, currently `1`                  |
+//! | 1      | 1    | kind, `0` = seeded, `1` = edges, `2` = esi     |
+//! | 2      | 2    | flags, see below                               |
+//! | 4      | 8    | seed, block index or encoding symbol id        |
+//! | 12     | 4    | degree (seeded), `0` for edges                 |
+//! | 16     | 8    | length of the whole object in bytes            |
+//! | 24     | 4    | block size in bytes                            |
+//! | 28     | 4    | payload length in bytes                        |
+//! | 32     | 8    | object id                                      |
+//! | 40     | 4    | CRC32C, only with `FLAG_CRC32C`                |
+//! | 40     | 32   | HMAC-SHA256 tag, only with `FLAG_HMAC`         |
+//! | 40..72 | n    | payload                                        |
+//!
+//! With `FLAG_CRC32C` set, the header carries the CRC32C of the first 40
+//! header bytes followed by the payload. With `FLAG_HMAC` it carries an
+//! HMAC of the same bytes instead, see `auth`. At most one of them is set,
+//! all other flags are reserved and must be `0`.
+//!
+//! The object parameters travel with every droplet, so a receiver can
+//! create its `Decoder` from the first packet it sees, see `ObjectDescriptor`.
+//! The object id lets a `Session` tell concurrent transfers apart.
+
+use crate::auth::{AuthKey, TAG_LEN};
+use std::{convert::TryFrom, error, fmt};
+
+/// Version of the packet format written by `Droplet::to_bytes`
+pub const VERSION: u8 = 1;
+
+/// Size of the packet header in bytes, without the checksum
+pub const HEADER_LEN: usize = 40;
+
+/// Size of the optional checksum which follows the header
+pub const CHECKSUM_LEN: usize = 4;
+
+/// Size of an encoded `ObjectDescriptor`
+pub const DESCRIPTOR_LEN: usize = 20;
+
+/// The packet carries a CRC32C of the header and payload
+pub const FLAG_CRC32C: u16 = 0x0001;
+
+/// The packet carries an HMAC-SHA256 tag of the header and payload
+pub const FLAG_HMAC: u16 = 0x0002;
+
+pub(crate) const KIND_SEEDED: u8 = 0;
+pub(crate) const KIND_EDGES: u8 = 1;
+pub(crate) const KIND_ESI: u8 = 2;
+
+/// Parameters of the object a droplet belongs to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectParams {
+    /// Identifies the object among concurrent transfers, chosen by the sender
+    pub object_id: u64,
+    /// Length of the object in bytes
+    pub len: usize,
+    /// Size of a single block, every payload has exactly this size
+    pub blocksize: usize,
+}
+
+impl ObjectParams {
+    /// Parameters for object id `0`
+    pub fn new(len: usize, blocksize: usize) -> ObjectParams {
+        ObjectParams {
+            object_id: 0,
+            len,
+            blocksize,
+        }
+    }
+
+    pub fn with_object_id(self, object_id: u64) -> ObjectParams {
+        ObjectParams { object_id, ..self }
+    }
+
+    /// Number of blocks the object is split into
+    pub fn cnt_blocks(&self) -> usize {
+        cnt_blocks(self.len, self.blocksize)
+    }
+
+    pub fn descriptor(&self) -> ObjectDescriptor {
+        ObjectDescriptor {
+            len: self.len as u64,
+            blocksize: u32::try_from(self.blocksize).expect("block size does not fit into u32"),
+        }
+    }
+}
+
+/// Upper bounds on the objects a receiver accepts.
+///
+/// The object parameters in a packet header come from the sender, and a
+/// decoder allocates the whole object up front. Without a bound, a single
+/// forged header could make the receiver exhaust its memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Largest object in bytes
+    pub max_object_size: usize,
+    /// Largest number of blocks of an object
+    pub max_blocks: usize,
+    /// Largest sum of the sizes of the objects decoded at once, only a
+    /// `Session` decodes more than one
+    pub max_total_size: usize,
+}
+
+impl Limits {
+    /// Fails with `ObjectTooLarge` if the object exceeds one of the limits
+    pub fn check(&self, params: &ObjectParams) -> Result<(), WireError> {
+        let cnt_blocks = params.cnt_blocks();
+        if params.len > self.max_object_size || cnt_blocks > self.max_blocks {
+            return Err(WireError::ObjectTooLarge {
+                len: params.len,
+                cnt_blocks,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Default for Limits {
+    /// Objects of up to 1 GiB in up to 2^20 blocks, and 1 GiB for all of them
+    fn default() -> Limits {
+        Limits {
+            max_object_size: 1 << 30,
+            max_blocks: 1 << 20,
+            max_total_size: 1 << 30,
+        }
+    }
+}
+
+/// Number of blocks of an object of `len` bytes, the last one may be short
+pub(crate) fn cnt_blocks(len: usize, blocksize: usize) -> usize {
+    len.div_ceil(blocksize)
+}
+
+/// Number of bytes of the object in block `idx`. Only the last block is
+/// shorter than `blocksize`, the droplets pad it with zeros.
+pub(crate) fn block_len(len: usize, blocksize: usize, idx: usize) -> usize {
+    let begin = idx * blocksize;
+    if len - begin < blocksize {
+        len - begin
+    } else {
+        blocksize
+    }
+}
+
+/// The layout of an object, independent of the platform: its length, the
+/// block size and the number of blocks.
+///
+/// Every packet header carries the length and the block size, so a receiver
+/// learns the descriptor from the first packet. It can also be sent ahead of
+/// the droplets, encoded in `DESCRIPTOR_LEN` bytes: the length as u64, the
+/// block size as u32 and the number of blocks as u64, all big-endian.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectDescriptor {
+    len: u64,
+    blocksize: u32,
+}
+
+impl ObjectDescriptor {
+    pub fn new(len: u64, blocksize: u32) -> Result<ObjectDescriptor, WireError> {
+        if blocksize == 0 {
+            return Err(WireError::InvalidBlocksize);
+        }
+        Ok(ObjectDescriptor { len, blocksize })
+    }
+
+    /// Length of the object in bytes
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn blocksize(&self) -> u32 {
+        self.blocksize
+    }
+
+    pub fn cnt_blocks(&self) -> u64 {
+        self.len.div_ceil(u64::from(self.blocksize))
+    }
+
+    /// Number of bytes of the object in block `idx`, the last block may be short
+    pub fn block_len(&self, idx: u64) -> u32 {
+        let begin = idx * u64::from(self.blocksize);
+        u32::try_from(
+            self.len
+                .saturating_sub(begin)
+                .min(u64::from(self.blocksize)),
+        )
+        .expect("bounded by the block size")
+    }
+
+    /// Parameters for a `Decoder` on this platform
+    pub fn params(&self) -> Result<ObjectParams, WireError> {
+        Ok(ObjectParams::new(
+            usize::try_from(self.len).map_err(|_| WireError::Overflow)?,
+            usize::try_from(self.blocksize).map_err(|_| WireError::Overflow)?,
+        ))
+    }
+
+    pub fn to_bytes(&self) -> [u8; DESCRIPTOR_LEN] {
+        let mut buf = [0; DESCRIPTOR_LEN];
+        buf[..8].copy_from_slice(&self.len.to_be_bytes());
+        buf[8..12].copy_from_slice(&self.blocksize.to_be_bytes());
+        buf[12..].copy_from_slice(&self.cnt_blocks().to_be_bytes());
+        buf
+    }
+
+    /// Parses a descriptor, the number of blocks must match the length and block size
+    pub fn from_bytes(buf: &[u8]) -> Result<ObjectDescriptor, WireError> {
+        if buf.len() < DESCRIPTOR_LEN {
+            return Err(WireError::Truncated {
+                needed: DESCRIPTOR_LEN,
+                available: buf.len(),
+            });
+        }
+        if buf.len() > DESCRIPTOR_LEN {
+            return Err(WireError::TrailingBytes(buf.len() - DESCRIPTOR_LEN));
+        }
+        let descriptor = ObjectDescriptor::new(read_u64(&buf[..8]), read_u32(&buf[8..12]))?;
+        if read_u64(&buf[12..]) != descriptor.cnt_blocks() {
+            return Err(WireError::InvalidDescriptor);
+        }
+        Ok(descriptor)
+    }
+}
+
+/// Errors returned when parsing a packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WireError {
+    /// The packet is shorter than the header or the announced payload
+    Truncated { needed: usize, available: usize },
+    /// The packet was written with an unknown version of the format
+    UnsupportedVersion(u8),
+    /// The droplet kind is not one of seeded, edges or esi
+    UnknownDropType(u8),
+    /// Reserved flags are set
+    UnsupportedFlags(u16),
+    /// The checksum does not match the contents of the packet
+    ChecksumMismatch,
+    /// The packet has no tag, or the tag does not match the key
+    Unauthenticated,
+    /// The block size is zero
+    InvalidBlocksize,
+    /// A seeded droplet announces degree zero, or another kind a degree at all
+    InvalidDegree,
+    /// The block index of an edges droplet lies outside of the object
+    BlockOutOfRange { index: u64, cnt_blocks: usize },
+    /// The payload size differs from the block size
+    PayloadLength { expected: usize, actual: usize },
+    /// There are bytes left after the payload
+    TrailingBytes(usize),
+    /// A header field does not fit into `usize` on this platform
+    Overflow,
+    /// The number of blocks of a descriptor does not match its length and block size
+    InvalidDescriptor,
+    /// The object exceeds the `Limits` of the receiver
+    ObjectTooLarge { len: usize, cnt_blocks: usize },
+}
+
+impl fmt::Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WireError::Truncated { needed, available } => write!(
+                f,
+                "packet truncated: need {} bytes, got {}",
+                needed, available
+            ),
+            WireError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            WireError::UnknownDropType(t) => write!(f, "unknown droplet kind {}", t),
+            WireError::UnsupportedFlags(flags) => write!(f, "unsupported flags {:#06x}", flags),
+            WireError::ChecksumMismatch => write!(f, "checksum mismatch, the packet is corrupt"),
+            WireError::Unauthenticated => write!(f, "packet is not authenticated"),
+            WireError::InvalidBlocksize => write!(f, "block size must not be zero"),
+            WireError::InvalidDegree => write!(f, "invalid degree for droplet kind"),
+            WireError::BlockOutOfRange { index, cnt_blocks } => write!(
+                f,
+                "block index {} out of range for {} blocks",
+                index, cnt_blocks
+            ),
+            WireError::PayloadLength { expected, actual } => write!(
+                f,
+                "payload has {} bytes, block size is {}",
+                actual, expected
+            ),
+            WireError::TrailingBytes(n) => write!(f, "{} trailing bytes after payload", n),
+            WireError::Overflow => write!(f, "header field does not fit into usize"),
+            WireError::InvalidDescriptor => {
+                write!(f, "block count does not match length and block size")
+            }
+            WireError::ObjectTooLarge { len, cnt_blocks } => write!(
+                f,
+                "object of {} bytes in {} blocks exceeds the limits",
+                len, cnt_blocks
+            ),
+        }
+    }
+}
+
+impl error::Error for WireError {}
+
+/// The integrity check which follows the fixed part of the header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Trailer {
+    None,
+    Checksum,
+    Tag,
+}
+
+/// Fields of a parsed header
+pub(crate) struct Header {
+    pub kind: u8,
+    pub id: u64,
+    pub degree: u32,
+    pub params: ObjectParams,
+    pub payload_len: usize,
+    pub trailer: Trailer,
+}
+
+impl Header {
+    /// Offset of the payload in the packet
+    pub fn payload_offset(&self) -> usize {
+        HEADER_LEN
+            + match self.trailer {
+                Trailer::None => 0,
+                Trailer::Checksum => CHECKSUM_LEN,
+                Trailer::Tag => TAG_LEN,
+            }
+    }
+}
+
+pub(crate) fn write_header(buf: &mut Vec<u8>, header: &Header) {
+    let blocksize =
+        u32::try_from(header.params.blocksize).expect("block size does not fit into u32");
+    let payload_len =
+        u32::try_from(header.payload_len).expect("payload length does not fit into u32");
+
+    let flags = match header.trailer {
+        Trailer::None => 0,
+        Trailer::Checksum => FLAG_CRC32C,
+        Trailer::Tag => FLAG_HMAC,
+    };
+
+    buf.push(VERSION);
+    buf.push(header.kind);
+    buf.extend_from_slice(&flags.to_be_bytes());
+    buf.extend_from_slice(&header.id.to_be_bytes());
+    buf.extend_from_slice(&header.degree.to_be_bytes());
+    buf.extend_from_slice(&(header.params.len as u64).to_be_bytes());
+    buf.extend_from_slice(&blocksize.to_be_bytes());
+    buf.extend_from_slice(&payload_len.to_be_bytes());
+    buf.extend_from_slice(&header.params.object_id.to_be_bytes());
+    // filled in by `write_trailer` once the payload is appended
+    buf.resize(buf.len() + header.payload_offset() - HEADER_LEN, 0);
+}
+
+/// Computes the checksum or tag of a complete packet
+pub(crate) fn write_trailer(buf: &mut [u8], header: &Header, key: Option<&AuthKey>) {
+    let offset = header.payload_offset();
+    let (fixed, rest) = buf.split_at_mut(HEADER_LEN);
+    let (trailer, payload) = rest.split_at_mut(offset - HEADER_LEN);
+    match header.trailer {
+        Trailer::None => {}
+        Trailer::Checksum => trailer.copy_from_slice(&crc(fixed, payload).to_be_bytes()),
+        Trailer::Tag => {
+            let key = key.expect("authenticated packets need a key");
+            trailer.copy_from_slice(&key.tag(fixed, payload));
+        }
+    }
+}
+
+/// Checks the tag of a packet whose header was read by `read_header`
+pub(crate) fn verify_tag(buf: &[u8], header: &Header, key: &AuthKey) -> Result<(), WireError> {
+    let offset = header.payload_offset();
+    if header.trailer == Trailer::Tag
+        && key.verify(&buf[..HEADER_LEN], &buf[offset..], &buf[HEADER_LEN..offset])
+    {
+        Ok(())
+    } else {
+        Err(WireError::Unauthenticated)
+    }
+}
+
+fn crc(header: &[u8], payload: &[u8]) -> u32 {
+    crc32c::crc32c_append(crc32c::crc32c(header), payload)
+}
+
+pub(crate) fn read_header(buf: &[u8]) -> Result<Header, WireError> {
+    if buf.len() < HEADER_LEN {
+        return Err(WireError::Truncated {
+            needed: HEADER_LEN,
+            available: buf.len(),
+        });
+    }
+
+    let version = buf[0];
+    if version != VERSION {
+        return Err(WireError::UnsupportedVersion(version));
+    }
+    let kind = buf[1];
+    if kind != KIND_SEEDED && kind != KIND_EDGES && kind != KIND_ESI {
+        return Err(WireError::UnknownDropType(kind));
+    }
+    let flags = u16::from_be_bytes([buf[2], buf[3]]);
+    let trailer = match flags {
+        0 => Trailer::None,
+        FLAG_CRC32C => Trailer::Checksum,
+        FLAG_HMAC => Trailer::Tag,
+        _ => return Err(WireError::UnsupportedFlags(flags)),
+    };
+
+    let id = read_u64(&buf[4..12]);
+    let degree = read_u32(&buf[12..16]);
+    let len = usize::try_from(read_u64(&buf[16..24])).map_err(|_| WireError::Overflow)?;
+    let blocksize = usize::try_from(read_u32(&buf[24..28])).map_err(|_| WireError::Overflow)?;
+    let payload_len = usize::try_from(read_u32(&buf[28..32])).map_err(|_| WireError::Overflow)?;
+    let object_id = read_u64(&buf[32..40]);
+
+    if blocksize == 0 {
+        return Err(WireError::InvalidBlocksize);
+    }
+    let params = ObjectParams {
+        object_id,
+        len,
+        blocksize,
+    };
+
+    if (kind == KIND_SEEDED) != (degree != 0) {
+        return Err(WireError::InvalidDegree);
+    }
+    if kind == KIND_ESI && id > u64::from(u32::MAX) {
+        return Err(WireError::Overflow);
+    }
+    if kind == KIND_EDGES && id >= params.cnt_blocks() as u64 {
+        return Err(WireError::BlockOutOfRange {
+            index: id,
+            cnt_blocks: params.cnt_blocks(),
+        });
+    }
+    if payload_len != blocksize {
+        return Err(WireError::PayloadLength {
+            expected: blocksize,
+            actual: payload_len,
+        });
+    }
+
+    let header = Header {
+        kind,
+        id,
+        degree,
+        params,
+        payload_len,
+        trailer,
+    };
+    let offset = header.payload_offset();
+    if buf.len() < offset + payload_len {
+        return Err(WireError::Truncated {
+            needed: offset + payload_len,
+            available: buf.len(),
+        });
+    }
+    if buf.len() > offset + payload_len {
+        return Err(WireError::TrailingBytes(buf.len() - offset - payload_len));
+    }
+    if trailer == Trailer::Checksum
+        && read_u32(&buf[HEADER_LEN..offset]) != crc(&buf[..HEADER_LEN], &buf[offset..])
+    {
+        return Err(WireError::ChecksumMismatch);
+    }
+
+    Ok(header)
+}
+
+fn read_u64(buf: &[u8]) -> u64 {
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(buf);
+    u64::from_be_bytes(bytes)
+}
+
+fn read_u32(buf: &[u8]) -> u32 {
+    let mut bytes = [0; 4];
+    bytes.copy_from_slice(buf);
+    u32::from_be_bytes(bytes)
+}
@@ -0,0 +1,128 @@
+//! XOR of blocks, the inner loop of encoding and decoding.
+//!
+//! `xor_into` picks the fastest kernel the CPU supports at runtime: AVX2 or
+//! SSE2 on x86, otherwise a portable loop over 64 bit words.
+
+use std::convert::TryInto;
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86 as arch;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64 as arch;
+
+/// An implementation of `xor_into`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kernel {
+    /// One byte at a time
+    Bytes,
+    /// Eight bytes at a time, on every platform
+    Words,
+    /// 16 bytes at a time
+    Sse2,
+    /// 32 bytes at a time
+    Avx2,
+}
+
+impl Kernel {
+    /// Every kernel, from the slowest to the fastest
+    pub const ALL: [Kernel; 4] = [Kernel::Bytes, Kernel::Words, Kernel::Sse2, Kernel::Avx2];
+
+    /// The fastest kernel the CPU supports
+    pub fn detect() -> Kernel {
+        if Kernel::Avx2.is_supported() {
+            Kernel::Avx2
+        } else if Kernel::Sse2.is_supported() {
+            Kernel::Sse2
+        } else {
+            Kernel::Words
+        }
+    }
+
+    /// Whether the CPU can run the kernel
+    pub fn is_supported(self) -> bool {
+        match self {
+            Kernel::Bytes | Kernel::Words => true,
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Kernel::Sse2 => is_x86_feature_detected!("sse2"),
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Kernel::Avx2 => is_x86_feature_detected!("avx2"),
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            Kernel::Sse2 | Kernel::Avx2 => false,
+        }
+    }
+
+    /// `dst ^= src` with this kernel. Panics if the lengths differ or the CPU
+    /// does not support the kernel.
+    pub fn xor_into(self, dst: &mut [u8], src: &[u8]) {
+        assert_eq!(dst.len(), src.len(), "blocks of different length");
+        assert!(
+            self.is_supported(),
+            "{:?} is not supported by the CPU",
+            self
+        );
+        match self {
+            Kernel::Bytes => xor_bytes(dst, src),
+            Kernel::Words => xor_words(dst, src),
+            // safe, the CPU supports the instructions
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Kernel::Sse2 => unsafe { xor_sse2(dst, src) },
+            #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+            Kernel::Avx2 => unsafe { xor_avx2(dst, src) },
+            #[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+            Kernel::Sse2 | Kernel::Avx2 => unreachable!("checked above"),
+        }
+    }
+}
+
+/// `dst ^= src` with the fastest kernel. Panics if the lengths differ.
+pub fn xor_into(dst: &mut [u8], src: &[u8]) {
+    Kernel::detect().xor_into(dst, src)
+}
+
+fn xor_bytes(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= s;
+    }
+}
+
+fn xor_words(dst: &mut [u8], src: &[u8]) {
+    let mut dst_words = dst.chunks_exact_mut(8);
+    let mut src_words = src.chunks_exact(8);
+    for (d, s) in (&mut dst_words).zip(&mut src_words) {
+        let x = u64::from_ne_bytes((&*d).try_into().expect("eight bytes"))
+            ^ u64::from_ne_bytes(s.try_into().expect("eight bytes"));
+        d.copy_from_slice(&x.to_ne_bytes());
+    }
+    xor_bytes(dst_words.into_remainder(), src_words.remainder());
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "sse2")]
+unsafe fn xor_sse2(dst: &mut [u8], src: &[u8]) {
+    use self::arch::{__m128i, _mm_loadu_si128, _mm_storeu_si128, _mm_xor_si128};
+
+    let end = dst.len() - dst.len() % 16;
+    for i in (0..end).step_by(16) {
+        let d = dst.as_mut_ptr().add(i) as *mut __m128i;
+        let s = src.as_ptr().add(i) as *const __m128i;
+        _mm_storeu_si128(d, _mm_xor_si128(_mm_loadu_si128(d), _mm_loadu_si128(s)));
+    }
+    xor_words(&mut dst[end..], &src[end..]);
+}
+
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+#[target_feature(enable = "avx2")]
+unsafe fn xor_avx2(dst: &mut [u8], src: &[u8]) {
+    use self::arch::{__m256i, _mm256_loadu_si256, _mm256_storeu_si256, _mm256_xor_si256};
+
+    let end = dst.len() - dst.len() % 32;
+    for i in (0..end).step_by(32) {
+        let d = dst.as_mut_ptr().add(i) as *mut __m256i;
+        let s = src.as_ptr().add(i) as *const __m256i;
+        _mm256_storeu_si256(
+            d,
+            _mm256_xor_si256(_mm256_loadu_si256(d), _mm256_loadu_si256(s)),
+        );
+    }
+    xor_sse2(&mut dst[end..], &src[end..]);
+}
@@ -0,0 +1,158 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::auth::{AuthKey, TAG_LEN};
+use self::fountaincode::decoder::{DecodeError, Decoder};
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::session::Session;
+use self::fountaincode::types::*;
+use self::fountaincode::wire::{ObjectParams, WireError, HEADER_LEN};
+use proptest::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::time::Duration;
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+#[test]
+fn auth_roundtrip_all_droptypes() {
+    let key = AuthKey::new(b"session key");
+    let params = ObjectParams::new(1000, 64).with_object_id(5);
+    let droptypes = [
+        DropType::Seeded(0xfeed, 3),
+        DropType::Edges(7),
+        DropType::Esi(70_000),
+    ];
+    for droptype in droptypes.iter() {
+        let drop = Droplet::new(droptype.clone(), vec![9; 64]);
+        let bytes = drop.to_bytes_authenticated(&params, &key);
+        assert_eq!(bytes.len(), HEADER_LEN + TAG_LEN + 64);
+
+        let (parsed, parsed_params) = Droplet::from_bytes_authenticated(&bytes, &key).unwrap();
+        assert_eq!(drop, parsed);
+        assert_eq!(params, parsed_params);
+
+        // parsing without the key skips the tag
+        assert_eq!(Droplet::from_bytes(&bytes).unwrap().0, drop);
+    }
+}
+
+#[test]
+fn auth_rejects_forged_packets() {
+    let key = AuthKey::new(b"session key");
+    let params = ObjectParams::new(1000, 64);
+    let drop = Droplet::new(DropType::Edges(1), vec![1; 64]);
+
+    let other_key = drop.to_bytes_authenticated(&params, &AuthKey::new(b"other key"));
+    assert_eq!(
+        Droplet::from_bytes_authenticated(&other_key, &key),
+        Err(WireError::Unauthenticated)
+    );
+
+    let plain = drop.to_bytes(&params);
+    assert_eq!(
+        Droplet::from_bytes_authenticated(&plain, &key),
+        Err(WireError::Unauthenticated)
+    );
+
+    let checksum = drop.to_bytes_with_checksum(&params);
+    assert_eq!(
+        Droplet::from_bytes_authenticated(&checksum, &key),
+        Err(WireError::Unauthenticated)
+    );
+
+    let mut payload = drop.to_bytes_authenticated(&params, &key);
+    let last = payload.len() - 1;
+    payload[last] ^= 1;
+    assert_eq!(
+        Droplet::from_bytes_authenticated(&payload, &key),
+        Err(WireError::Unauthenticated)
+    );
+}
+
+#[test]
+fn auth_decoder_drops_forged_droplets() {
+    let key = AuthKey::new(b"session key");
+    let forger = AuthKey::new(b"guessed key");
+    let buf = random_data(10_000);
+    let to_compare = buf.clone();
+
+    let params = ObjectParams::new(buf.len(), 100);
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Random);
+    let mut dec = Decoder::new(params.len, params.blocksize);
+    dec.set_auth_key(key.clone());
+    let mut cnt_forged = 0;
+
+    loop {
+        // a forged packet before every genuine one, without a tag or with a guessed key
+        let idx = thread_rng().gen_range(0, params.cnt_blocks());
+        let forged = Droplet::new(DropType::Edges(idx), random_data(params.blocksize));
+        let packet = if cnt_forged % 2 == 0 {
+            forged.to_bytes(&params)
+        } else {
+            forged.to_bytes_authenticated(&params, &forger)
+        };
+        assert!(matches!(
+            dec.catch_packet(&packet),
+            Err(DecodeError::Wire(WireError::Unauthenticated))
+        ));
+        cnt_forged += 1;
+
+        let packet = enc.next().to_bytes_authenticated(&params, &key);
+        match dec.catch_packet(&packet).unwrap() {
+            CatchResult::Missing(stats) => assert_eq!(stats.cnt_unauthenticated, cnt_forged),
+            CatchResult::Finished(data, stats) => {
+                assert_eq!(stats.cnt_unauthenticated, cnt_forged);
+                assert_eq!(to_compare, data);
+                return;
+            }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+        }
+    }
+}
+
+#[test]
+fn auth_session_counts_forged_packets() {
+    let key = AuthKey::new(b"session key");
+    let buf = random_data(2000);
+    let to_compare = buf.clone();
+
+    let params = ObjectParams::new(buf.len(), 100).with_object_id(1);
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Systematic);
+    let mut session = Session::new(Duration::from_secs(60));
+    session.set_auth_key(key.clone());
+
+    let forged = Droplet::new(DropType::Edges(0), vec![0; 100]).to_bytes(&params);
+    assert_eq!(
+        session.receive(&forged).err(),
+        Some(WireError::Unauthenticated)
+    );
+    assert_eq!(session.cnt_unauthenticated(), 1);
+    assert_eq!(session.cnt_active(), 0);
+
+    loop {
+        let packet = enc.next().to_bytes_authenticated(&params, &key);
+        if let Some((1, CatchResult::Finished(data, _))) = session.receive(&packet).unwrap() {
+            assert_eq!(to_compare, data);
+            break;
+        }
+    }
+    assert_eq!(session.cnt_unauthenticated(), 1);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10000))]
+    #[test]
+    fn auth_bit_flip_prop_test(seed in any::<u64>(), pos in 0..HEADER_LEN + TAG_LEN + 32, bit in 0..8u8) {
+        let key = AuthKey::new(b"prop key");
+        let params = ObjectParams::new(500, 32);
+        let mut bytes = Droplet::new(DropType::Seeded(seed, 4), vec![0x5a; 32]).to_bytes_authenticated(&params, &key);
+        bytes[pos] ^= 1 << bit;
+        prop_assert!(Droplet::from_bytes_authenticated(&bytes, &key).is_err());
+    }
+}
@@ -0,0 +1,335 @@
+extern crate rand;
+
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn fountain(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_fountain"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn scratch_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "fountain-{}-{}-{}",
+        name,
+        std::process::id(),
+        thread_rng().gen::<u32>()
+    ));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn write_input(dir: &Path, len: usize) -> (String, Vec<u8>) {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    let path = dir.join("input.bin");
+    fs::write(&path, s.as_bytes()).unwrap();
+    (path.to_str().unwrap().to_string(), s.into_bytes())
+}
+
+#[test]
+fn cli_stream_roundtrip() {
+    let dir = scratch_dir("stream");
+    let (input, data) = write_input(&dir, 50_000);
+    let stream = dir.join("droplets.bin");
+    let output = dir.join("output.bin");
+
+    let enc = fountain(&[
+        "encode",
+        "--blocksize",
+        "512",
+        "--overhead",
+        "1.0",
+        "--seed",
+        "1",
+        &input,
+        stream.to_str().unwrap(),
+    ]);
+    assert!(enc.status.success(), "{:?}", enc);
+
+    let dec = fountain(&["decode", output.to_str().unwrap(), stream.to_str().unwrap()]);
+    assert!(dec.status.success(), "{:?}", dec);
+    assert_eq!(fs::read(&output).unwrap(), data);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_split_decodes_from_a_subset() {
+    let dir = scratch_dir("split");
+    let (input, data) = write_input(&dir, 20_000);
+    let droplets = dir.join("droplets");
+    let output = dir.join("output.bin");
+
+    let enc = fountain(&[
+        "encode",
+        "--blocksize",
+        "100",
+        "--distribution",
+        "ideal",
+        "--overhead",
+        "2.0",
+        "--split",
+        &input,
+        droplets.to_str().unwrap(),
+    ]);
+    assert!(enc.status.success(), "{:?}", enc);
+    assert_eq!(fs::read_dir(&droplets).unwrap().count(), 600);
+
+    // lose every third droplet
+    for (i, entry) in fs::read_dir(&droplets).unwrap().enumerate() {
+        if i % 3 == 0 {
+            fs::remove_file(entry.unwrap().path()).unwrap();
+        }
+    }
+
+    let dec = fountain(&[
+        "decode",
+        "--elimination",
+        output.to_str().unwrap(),
+        droplets.to_str().unwrap(),
+    ]);
+    assert!(dec.status.success(), "{:?}", dec);
+    assert_eq!(fs::read(&output).unwrap(), data);
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_decode_fails_without_enough_droplets() {
+    let dir = scratch_dir("short");
+    let (input, _) = write_input(&dir, 10_000);
+    let stream = dir.join("droplets.bin");
+    let output = dir.join("output.bin");
+
+    let enc = fountain(&[
+        "encode",
+        "--blocksize",
+        "100",
+        "--overhead",
+        "0",
+        &input,
+        stream.to_str().unwrap(),
+    ]);
+    assert!(enc.status.success(), "{:?}", enc);
+
+    // half of the droplets, with a truncated packet at the end
+    let bytes = fs::read(&stream).unwrap();
+    fs::write(&stream, &bytes[..bytes.len() / 2]).unwrap();
+    let dec = fountain(&["decode", output.to_str().unwrap(), stream.to_str().unwrap()]);
+    assert!(!dec.status.success());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_inspect_lists_headers() {
+    let dir = scratch_dir("inspect");
+    let (input, _) = write_input(&dir, 1000);
+    let stream = dir.join("droplets.bin");
+
+    let enc = fountain(&[
+        "encode",
+        "--blocksize",
+        "100",
+        "--overhead",
+        "0.5",
+        "--systematic",
+        "--object-id",
+        "42",
+        &input,
+        stream.to_str().unwrap(),
+    ]);
+    assert!(enc.status.success(), "{:?}", enc);
+
+    let out = fountain(&["inspect", stream.to_str().unwrap()]);
+    assert!(out.status.success(), "{:?}", out);
+    let stdout = String::from_utf8(out.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 15);
+    assert!(lines[0].contains("object=42 len=1000 blocksize=100 edges block=0"));
+    assert!(lines.iter().all(|line| line.contains("object=42")));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_rejects_bad_arguments() {
+    assert!(!fountain(&[]).status.success());
+    assert!(!fountain(&["encode", "--blocksize"]).status.success());
+    assert!(!fountain(&["encode", "--bogus", "a", "b"]).status.success());
+    assert!(!fountain(&["encode", "--blocksize", "x", "a", "b"])
+        .status
+        .success());
+    assert!(fountain(&["--help"]).status.success());
+}
+
+#[test]
+fn cli_checksum_and_digest() {
+    let dir = scratch_dir("digest");
+    let (input, data) = write_input(&dir, 20_000);
+    let stream = dir.join("droplets.bin");
+    let output = dir.join("output.bin");
+
+    let enc = fountain(&[
+        "encode",
+        "--blocksize",
+        "200",
+        "--overhead",
+        "1.0",
+        "--systematic",
+        "--checksum",
+        &input,
+        stream.to_str().unwrap(),
+    ]);
+    assert!(enc.status.success(), "{:?}", enc);
+    let stdout = String::from_utf8(enc.stdout).unwrap();
+    let sha256 = stdout.trim().strip_prefix("sha256 ").unwrap().to_string();
+
+    // corrupt the payload of the first droplet, the checksum catches it
+    let mut bytes = fs::read(&stream).unwrap();
+    bytes[100] ^= 0xff;
+    fs::write(&stream, &bytes).unwrap();
+
+    let dec = fountain(&[
+        "decode",
+        "--sha256",
+        &sha256,
+        output.to_str().unwrap(),
+        stream.to_str().unwrap(),
+    ]);
+    assert!(dec.status.success(), "{:?}", dec);
+    assert!(String::from_utf8(dec.stderr)
+        .unwrap()
+        .contains("skipped 1 corrupt packets"));
+    assert_eq!(fs::read(&output).unwrap(), data);
+
+    let wrong = "0".repeat(64);
+    let dec = fountain(&[
+        "decode",
+        "--sha256",
+        &wrong,
+        output.to_str().unwrap(),
+        stream.to_str().unwrap(),
+    ]);
+    assert!(!dec.status.success());
+    assert!(!output.exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_optimize_writes_a_preset() {
+    let dir = scratch_dir("optimize");
+    let (input, data) = write_input(&dir, 10_000);
+    let preset = dir.join("preset.txt");
+    let stream = dir.join("droplets.bin");
+    let output = dir.join("output.bin");
+
+    let opt = fountain(&[
+        "optimize",
+        "--blocks",
+        "100",
+        "--loss",
+        "0.1",
+        "--trials",
+        "5",
+        "--objective",
+        "p95",
+        "--preset",
+        preset.to_str().unwrap(),
+    ]);
+    assert!(opt.status.success(), "{:?}", opt);
+    let text = fs::read_to_string(&preset).unwrap();
+    assert!(text.lines().nth(1).unwrap().starts_with("robust c="));
+
+    let enc = fountain(&[
+        "encode",
+        "--blocksize",
+        "100",
+        "--overhead",
+        "2.0",
+        "--preset",
+        preset.to_str().unwrap(),
+        &input,
+        stream.to_str().unwrap(),
+    ]);
+    assert!(enc.status.success(), "{:?}", enc);
+    let dec = fountain(&["decode", output.to_str().unwrap(), stream.to_str().unwrap()]);
+    assert!(dec.status.success(), "{:?}", dec);
+    assert_eq!(fs::read(&output).unwrap(), data);
+
+    assert!(!fountain(&["optimize", "--loss", "1.5"]).status.success());
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Encodes 20000 bytes in blocks of 200 with plenty of overhead, returns
+/// the stream and the data
+fn encode_stream(dir: &Path) -> (PathBuf, Vec<u8>) {
+    let (input, data) = write_input(dir, 20_000);
+    let stream = dir.join("droplets.bin");
+    let enc = fountain(&[
+        "encode",
+        "--blocksize",
+        "200",
+        "--overhead",
+        "1.0",
+        &input,
+        stream.to_str().unwrap(),
+    ]);
+    assert!(enc.status.success(), "{:?}", enc);
+    (stream, data)
+}
+
+#[test]
+fn cli_decode_does_not_trust_the_first_header() {
+    let dir = scratch_dir("header");
+    let (stream, data) = encode_stream(&dir);
+    let output = dir.join("output.bin");
+    let bytes = fs::read(&stream).unwrap();
+
+    // the object length follows the 4 byte length prefix and 16 header bytes
+    for &len in &[1u64 << 46, data.len() as u64 + 1] {
+        let mut corrupt = bytes.clone();
+        corrupt[20..28].copy_from_slice(&len.to_be_bytes());
+        fs::write(&stream, &corrupt).unwrap();
+        let dec = fountain(&["decode", output.to_str().unwrap(), stream.to_str().unwrap()]);
+        assert!(dec.status.success(), "{:?}", dec);
+        assert_eq!(fs::read(&output).unwrap(), data);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cli_decode_ends_a_file_at_a_bad_packet() {
+    let dir = scratch_dir("tail");
+    let (stream, data) = encode_stream(&dir);
+    let output = dir.join("output.bin");
+    let bytes = fs::read(&stream).unwrap();
+
+    // a length prefix of 4 GiB, and a packet cut off by the end of the file,
+    // in a file read before the stream
+    let mut oversized = u32::MAX.to_be_bytes().to_vec();
+    oversized.extend_from_slice(&bytes[4..]);
+    let truncated = bytes[..100].to_vec();
+    let bad = dir.join("bad.bin");
+    for (corrupt, warning) in &[(oversized, "is skipped"), (truncated, "truncated packet")] {
+        fs::write(&bad, corrupt).unwrap();
+        let dec = fountain(&[
+            "decode",
+            output.to_str().unwrap(),
+            bad.to_str().unwrap(),
+            stream.to_str().unwrap(),
+        ]);
+        assert!(dec.status.success(), "{:?}", dec);
+        assert!(String::from_utf8_lossy(&dec.stderr).contains(warning));
+        assert_eq!(fs::read(&output).unwrap(), data);
+    }
+
+    fs::remove_dir_all(&dir).unwrap();
+}
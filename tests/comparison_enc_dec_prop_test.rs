@@ -23,13 +23,13 @@ proptest! {
         let buf = s.into_bytes();
         let len = buf.len();
 
-        let mut dec = Decoder::new(len, chunk_len as usize);
-
         let mut renc = RobustEncoder::new(buf.clone(), chunk_len as usize, EncoderType::Systematic, 0.2, None, 0.05);
+        let mut dec = Decoder::new(len, chunk_len as usize);
         let mut sw = Stopwatch::start_new();
         let res1 = robust_run(&mut renc, &mut dec);
         let t1 = sw.elapsed();
         let mut ienc = IdealEncoder::new(buf.clone(), chunk_len as usize, EncoderType::Systematic);
+        let mut dec = Decoder::new(len, chunk_len as usize);
         sw.restart();
         let res2 = ideal_run(&mut ienc, &mut dec);
         let t2 = sw.elapsed();
@@ -54,16 +54,17 @@ proptest! {
 
         let losses = vec![0.1, 0.3, 0.5, 0.9];
 
-        let mut dec = Decoder::new(len, chunk_len as usize);
         let mut res1: Vec<u8> = vec![];
         let mut res2: Vec<u8> = vec![];
 
         for loss in losses {
             let mut renc = RobustEncoder::new(buf.clone(), chunk_len as usize, EncoderType::Systematic, 0.2, None, 0.05);
+            let mut dec = Decoder::new(len, chunk_len as usize);
             let mut sw = Stopwatch::start_new();
             res1 = robust_run_lossy(&mut renc, &mut dec, loss);
             let t1 = sw.elapsed();
             let mut ienc = IdealEncoder::new(buf.clone(), chunk_len as usize, EncoderType::Systematic);
+            let mut dec = Decoder::new(len, chunk_len as usize);
             sw.restart();
             res2 = ideal_run_lossy(&mut ienc, &mut dec, loss);
             let t2 = sw.elapsed();
@@ -79,7 +80,7 @@ proptest! {
 fn robust_run(enc: &mut RobustEncoder, dec: &mut Decoder) -> Vec<u8> {
     loop {
         let drop = enc.next();
-        match dec.catch(drop) {
+        match dec.catch(drop).unwrap() {
             CatchResult::Missing(stats) => {
                 println!("robust unknown_chunks: {:?}", stats.unknown_chunks);
             }
@@ -87,6 +88,7 @@ fn robust_run(enc: &mut RobustEncoder, dec: &mut Decoder) -> Vec<u8> {
                 println!("robust_overhead: {:?}", stats.overhead);
                 break data;
             }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
         }
     }
 }
@@ -94,7 +96,7 @@ fn robust_run(enc: &mut RobustEncoder, dec: &mut Decoder) -> Vec<u8> {
 fn ideal_run(enc: &mut IdealEncoder, dec: &mut Decoder) -> Vec<u8> {
     let out = loop {
         let drop = enc.next();
-        match dec.catch(drop) {
+        match dec.catch(drop).unwrap() {
             CatchResult::Missing(stats) => {
                 println!("ideal unknown_chunks: {:?}", stats.unknown_chunks);
             }
@@ -102,6 +104,7 @@ fn ideal_run(enc: &mut IdealEncoder, dec: &mut Decoder) -> Vec<u8> {
                 println!("ideal_overhead: {:?}", stats.overhead);
                 break data;
             }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
         }
     };
     out
@@ -113,7 +116,7 @@ fn robust_run_lossy(enc: &mut RobustEncoder, dec: &mut Decoder, loss: f32) -> Ve
     loop {
         if loss_rng.gen::<f32>() > loss {
             let drop = enc.next();
-            match dec.catch(drop) {
+            match dec.catch(drop).unwrap() {
                 CatchResult::Missing(_stats) => {
                     // println!("Missing blocks {:?}", stats);
                 }
@@ -121,6 +124,7 @@ fn robust_run_lossy(enc: &mut RobustEncoder, dec: &mut Decoder, loss: f32) -> Ve
                     println!("robust_overhead: {:?}", stats.overhead);
                     break data;
                 }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
             }
         }
     }
@@ -131,7 +135,7 @@ fn ideal_run_lossy(enc: &mut IdealEncoder, dec: &mut Decoder, loss: f32) -> Vec<
     loop {
         if loss_rng.gen::<f32>() > loss {
             let drop = enc.next();
-            match dec.catch(drop) {
+            match dec.catch(drop).unwrap() {
                 CatchResult::Missing(_stats) => {
                     // println!("Missing blocks {:?}", stats);
                 }
@@ -139,6 +143,7 @@ fn ideal_run_lossy(enc: &mut IdealEncoder, dec: &mut Decoder, loss: f32) -> Vec<
                     println!("ideal_overhead: {:?}", stats.overhead);
                     break data;
                 }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
             }
         }
     }
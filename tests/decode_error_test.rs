@@ -0,0 +1,107 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::{DecodeError, Decoder};
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::types::*;
+use proptest::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+#[test]
+fn invalid_droplets_are_rejected() {
+    let mut dec = Decoder::new(1000, 100);
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Edges(10), vec![0; 100])),
+        Err(DecodeError::BlockOutOfRange {
+            index: 10,
+            cnt_blocks: 10
+        })
+    ));
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Edges(0), vec![0; 99])),
+        Err(DecodeError::PayloadLength {
+            expected: 100,
+            actual: 99
+        })
+    ));
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Seeded(7, 3), vec![0; 101])),
+        Err(DecodeError::PayloadLength {
+            expected: 100,
+            actual: 101
+        })
+    ));
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Seeded(7, 0), vec![0; 100])),
+        Err(DecodeError::InvalidDegree)
+    ));
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Esi(0), vec![0; 100])),
+        Err(DecodeError::ParameterMismatch)
+    ));
+    // rejected droplets are not counted
+    assert_eq!(dec.statistics().cnt_droplets, 0);
+}
+
+#[test]
+fn catching_after_the_end_fails() {
+    let buf = random_data(1000);
+    let mut enc = IdealEncoder::new(buf.clone(), 100, EncoderType::Systematic);
+    let mut dec = Decoder::new(buf.len(), 100);
+    loop {
+        if let CatchResult::Finished(data, _) = dec.catch(enc.next()).unwrap() {
+            assert_eq!(data, buf);
+            break;
+        }
+    }
+    assert!(matches!(
+        dec.catch(enc.next()),
+        Err(DecodeError::AlreadyFinished)
+    ));
+}
+
+fn droptype() -> impl Strategy<Value = DropType> {
+    prop_oneof![
+        (any::<u64>(), 0usize..64).prop_map(|(seed, degree)| DropType::Seeded(seed, degree)),
+        any::<u64>().prop_map(|seed| DropType::Seeded(seed, usize::MAX)),
+        (0usize..64).prop_map(DropType::Edges),
+        any::<usize>().prop_map(DropType::Edges),
+        any::<u32>().prop_map(DropType::Esi),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn garbage_does_not_break_the_decoder(
+        drops in prop::collection::vec((droptype(), 0usize..80), 0..50),
+        mode in prop_oneof![Just(DecoderMode::Peeling), Just(DecoderMode::Elimination)],
+    ) {
+        let buf = random_data(1000);
+        let mut dec = Decoder::with_mode(buf.len(), 40, mode);
+        for (droptype, len) in drops {
+            let data = (0..len).map(|_| thread_rng().gen()).collect();
+            let _ = dec.catch(Droplet::new(droptype, data));
+        }
+
+        // the garbage may have corrupted the object, but the decoder still finishes
+        let mut enc = IdealEncoder::new(buf, 40, EncoderType::Systematic);
+        let mut cnt_sent = 0;
+        while !dec.is_finished() {
+            match dec.catch(enc.next()) {
+                Ok(_) => {}
+                Err(DecodeError::AlreadyFinished) => break,
+                Err(err) => prop_assert!(false, "{}", err),
+            }
+            cnt_sent += 1;
+            prop_assert!(cnt_sent <= 25, "a systematic encoder is done after 25 droplets");
+        }
+    }
+}
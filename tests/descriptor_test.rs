@@ -0,0 +1,159 @@
+extern crate fountaincode;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::lt_encoder::LtEncoder;
+use self::fountaincode::storage::BlockSource;
+use self::fountaincode::types::*;
+use self::fountaincode::wire::{Limits, ObjectDescriptor, ObjectParams, WireError, DESCRIPTOR_LEN};
+use proptest::prelude::*;
+use std::io;
+
+/// An object of zeros which is never stored
+struct Zeros;
+
+impl BlockSource for Zeros {
+    fn read_at(&mut self, _offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        for b in buf.iter_mut() {
+            *b = 0;
+        }
+        Ok(())
+    }
+}
+
+#[test]
+fn block_count_is_exact_beyond_f32() {
+    // 2^24 + 1 is not representable as f32, the count used to be off by one
+    let len = (1 << 24) + 1;
+    assert_eq!(ObjectParams::new(len, 1).cnt_blocks(), len);
+    let enc = LtEncoder::builder(1).seed(0).build_with_source(Zeros, len);
+    assert_eq!(enc.cnt_blocks(), len);
+
+    let descriptor = ObjectDescriptor::new(u64::MAX, 1 << 20).unwrap();
+    assert_eq!(descriptor.cnt_blocks(), (1 << 44));
+    assert_eq!(
+        descriptor.block_len(descriptor.cnt_blocks() - 1),
+        (1 << 20) - 1
+    );
+}
+
+#[test]
+fn descriptor_short_final_block() {
+    let descriptor = ObjectParams::new(1000, 64).descriptor();
+    assert_eq!(descriptor.len(), 1000);
+    assert_eq!(descriptor.blocksize(), 64);
+    assert_eq!(descriptor.cnt_blocks(), 16);
+    assert_eq!(descriptor.block_len(0), 64);
+    assert_eq!(descriptor.block_len(14), 64);
+    assert_eq!(descriptor.block_len(15), 40);
+    assert_eq!(descriptor.block_len(16), 0);
+
+    let exact = ObjectDescriptor::new(1024, 64).unwrap();
+    assert_eq!(exact.cnt_blocks(), 16);
+    assert_eq!(exact.block_len(15), 64);
+}
+
+#[test]
+fn descriptor_rejects_invalid_bytes() {
+    assert_eq!(
+        ObjectDescriptor::new(10, 0),
+        Err(WireError::InvalidBlocksize)
+    );
+
+    let mut bytes = ObjectDescriptor::new(1000, 64).unwrap().to_bytes();
+    assert!(matches!(
+        ObjectDescriptor::from_bytes(&bytes[..DESCRIPTOR_LEN - 1]),
+        Err(WireError::Truncated { .. })
+    ));
+    assert_eq!(
+        ObjectDescriptor::from_bytes(&[bytes.as_ref(), &[0]].concat()),
+        Err(WireError::TrailingBytes(1))
+    );
+    bytes[DESCRIPTOR_LEN - 1] = 17;
+    assert_eq!(
+        ObjectDescriptor::from_bytes(&bytes),
+        Err(WireError::InvalidDescriptor)
+    );
+}
+
+#[test]
+fn decoder_from_the_first_packet() {
+    let data: Vec<u8> = (0..5000).map(|i| (i % 251) as u8).collect();
+    let params = ObjectParams::new(data.len(), 128);
+    let mut enc = LtEncoder::builder(128)
+        .encodertype(EncoderType::Systematic)
+        .build(data.clone());
+
+    // the receiver knows nothing but the packets
+    let packets: Vec<Vec<u8>> = (0..60).map(|_| enc.next().to_bytes(&params)).collect();
+    let (_, first) = Droplet::from_bytes(&packets[0]).unwrap();
+    let mut dec =
+        Decoder::from_descriptor(&first.descriptor(), DecoderMode::Peeling, Limits::default())
+            .unwrap();
+    for packet in packets.iter() {
+        if let CatchResult::Finished(decoded, _) = dec.catch_packet(packet).unwrap() {
+            assert_eq!(decoded, data);
+            return;
+        }
+    }
+    panic!("the object was not recovered");
+}
+
+#[test]
+fn decoder_from_an_oversized_descriptor() {
+    // the object would be allocated up front
+    let descriptor = ObjectDescriptor::new(1 << 50, 16).unwrap();
+    assert!(matches!(
+        Decoder::from_descriptor(&descriptor, DecoderMode::Peeling, Limits::default()),
+        Err(WireError::ObjectTooLarge { .. })
+    ));
+    let limits = Limits {
+        max_object_size: 10_000,
+        max_blocks: 10,
+        ..Limits::default()
+    };
+    let descriptor = ObjectDescriptor::new(5000, 100).unwrap();
+    assert!(matches!(
+        Decoder::from_descriptor(&descriptor, DecoderMode::Peeling, limits),
+        Err(WireError::ObjectTooLarge {
+            len: 5000,
+            cnt_blocks: 50
+        })
+    ));
+    let descriptor = ObjectDescriptor::new(1000, 100).unwrap();
+    assert!(Decoder::from_descriptor(&descriptor, DecoderMode::Peeling, limits).is_ok());
+}
+
+proptest! {
+    #[test]
+    fn descriptor_roundtrip_prop_test(len in any::<u64>(), blocksize in 1..u32::MAX) {
+        let descriptor = ObjectDescriptor::new(len, blocksize).unwrap();
+        let bytes = descriptor.to_bytes();
+        prop_assert_eq!(ObjectDescriptor::from_bytes(&bytes), Ok(descriptor));
+
+        let cnt = descriptor.cnt_blocks();
+        if cnt > 0 {
+            let last = u64::from(descriptor.block_len(cnt - 1));
+            prop_assert!(last > 0 && last <= u64::from(blocksize));
+            prop_assert_eq!((cnt - 1) as u128 * u128::from(blocksize) + u128::from(last), u128::from(len));
+        }
+    }
+
+    #[test]
+    fn short_final_block_roundtrip_prop_test(len in 1..3000usize, blocksize in 1..200usize) {
+        let data: Vec<u8> = (0..len).map(|i| (i % 253) as u8 + 1).collect();
+        let mut enc = LtEncoder::builder(blocksize).encodertype(EncoderType::Systematic).build(data.clone());
+        let mut dec = Decoder::new(len, blocksize);
+        loop {
+            match dec.catch(enc.next()).unwrap() {
+                CatchResult::Missing(_) => {}
+                CatchResult::Finished(decoded, _) => {
+                    prop_assert_eq!(decoded, data);
+                    break;
+                }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+            }
+        }
+    }
+}
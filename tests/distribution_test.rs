@@ -0,0 +1,120 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::custom_distribution::{CustomDistribution, DistributionError};
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::lt_encoder::LtEncoder;
+use self::fountaincode::soliton::Soliton;
+use self::fountaincode::types::*;
+use proptest::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+fn decode<E: Encoder>(enc: &mut E, len: usize, blocksize: usize) -> Vec<u8> {
+    let mut dec = Decoder::new(len, blocksize);
+    loop {
+        match dec.catch(enc.next()).unwrap() {
+            CatchResult::Missing(_) => {}
+            CatchResult::Finished(data, _) => return data,
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+        }
+    }
+}
+
+#[test]
+fn distribution_validation() {
+    assert_eq!(
+        CustomDistribution::new(&[], 0).err(),
+        Some(DistributionError::Empty)
+    );
+    assert_eq!(
+        CustomDistribution::new(&[0.0, 0.0], 0).err(),
+        Some(DistributionError::Empty)
+    );
+    assert_eq!(
+        CustomDistribution::new(&[0.5, 0.5], 0).err(),
+        Some(DistributionError::DegreeZero)
+    );
+    assert_eq!(
+        CustomDistribution::new(&[0.0, 1.5, -0.5], 0).err(),
+        Some(DistributionError::InvalidProbability {
+            degree: 2,
+            probability: -0.5
+        })
+    );
+    assert!(matches!(
+        CustomDistribution::new(&[0.0, f64::NAN], 0),
+        Err(DistributionError::InvalidProbability { degree: 1, .. })
+    ));
+    assert_eq!(
+        CustomDistribution::new(&[0.0, 0.5, 0.4], 0).err(),
+        Some(DistributionError::NotNormalized(0.9))
+    );
+    assert!(CustomDistribution::from_pairs(&[(1, 0.25), (4, 0.75)], 0).is_ok());
+}
+
+#[test]
+fn distribution_maps_to_degrees() {
+    let dist = CustomDistribution::new(&[0.0, 0.25, 0.0, 0.75, 0.0, 0.0], 0).unwrap();
+    assert_eq!(dist.max_degree(), 3);
+    assert_eq!(dist.degree(0.0), 1);
+    assert_eq!(dist.degree(0.2499), 1);
+    assert_eq!(dist.degree(0.25), 3);
+    assert_eq!(dist.degree(0.9999), 3);
+}
+
+#[test]
+fn raptor_distribution_frequencies() {
+    let mut dist = CustomDistribution::raptor(7);
+    assert_eq!(dist.max_degree(), 66);
+    let cnt = 100_000;
+    let mut cnt_degree_2 = 0;
+    for _ in 0..cnt {
+        let degree = dist.next();
+        assert!((1..=66).contains(&degree));
+        if degree == 2 {
+            cnt_degree_2 += 1;
+        }
+    }
+    let freq = cnt_degree_2 as f64 / cnt as f64;
+    assert!((freq - 0.49357).abs() < 0.01, "{}", freq);
+}
+
+#[test]
+fn lt_encoder_with_raptor_distribution() {
+    let buf = random_data(20_000);
+    let to_compare = buf.clone();
+    let mut enc = LtEncoder::builder(100)
+        .encodertype(EncoderType::Systematic)
+        .seed(42)
+        .distribution(|_, seed| CustomDistribution::raptor(seed))
+        .build(buf);
+    assert_eq!(decode(&mut enc, to_compare.len(), 100), to_compare);
+}
+
+#[test]
+fn lt_encoder_with_custom_distribution() {
+    let buf = random_data(5_000);
+    let to_compare = buf.clone();
+    let dist = CustomDistribution::new(&[0.0, 0.1, 0.5, 0.2, 0.2], 3).unwrap();
+    let mut enc = LtEncoder::builder(64)
+        .seed(3)
+        .distribution(|_, _| dist)
+        .build(buf);
+    assert_eq!(decode(&mut enc, to_compare.len(), 64), to_compare);
+}
+
+proptest! {
+    #[test]
+    fn custom_distribution_degree_in_range(u in 0.0..1.0f64) {
+        let dist = CustomDistribution::raptor(0);
+        let degree = dist.degree(u);
+        prop_assert!((1..=dist.max_degree()).contains(&degree));
+    }
+}
@@ -0,0 +1,183 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::online_decoder::OnlineDecoder;
+use self::fountaincode::online_encoder::OnlineEncoder;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::types::*;
+use proptest::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+fn run<E: Encoder>(enc: &mut E, dec: &mut Decoder, loss: f32, to_compare: &[u8]) -> usize {
+    let mut loss_rng = thread_rng();
+    loop {
+        let drop = enc.next();
+        if loss_rng.gen::<f32>() > loss {
+            if let CatchResult::Finished(data, stats) = dec.catch(drop).unwrap() {
+                assert_eq!(to_compare, &data[..]);
+                assert_eq!(stats.cnt_peeled + stats.cnt_eliminated, stats.cnt_chunks);
+                return stats.cnt_droplets;
+            }
+        }
+    }
+}
+
+#[test]
+fn elimination_needs_fewer_droplets() {
+    let len = 20_000;
+    let chunk_len = 100;
+    let rounds = 10;
+    let mut peeling = 0;
+    let mut elimination = 0;
+    for seed in 0..rounds {
+        let buf = random_data(len);
+        let mut enc = IdealEncoder::with_seed(buf.clone(), chunk_len, EncoderType::Random, seed);
+        let mut dec = Decoder::with_mode(len, chunk_len, DecoderMode::Peeling);
+        peeling += run(&mut enc, &mut dec, 0.0, &buf);
+
+        let mut enc = IdealEncoder::with_seed(buf.clone(), chunk_len, EncoderType::Random, seed);
+        let mut dec = Decoder::with_mode(len, chunk_len, DecoderMode::Elimination);
+        elimination += run(&mut enc, &mut dec, 0.0, &buf);
+    }
+    println!("peeling: {}, elimination: {}", peeling, elimination);
+    assert!(elimination < peeling);
+}
+
+#[test]
+fn elimination_robust_needs_near_k_droplets() {
+    let len = 20_000;
+    let chunk_len = 100;
+    let buf = random_data(len);
+    // the droplet counts only depend on the seeds
+    let mut cnt = 0;
+    for seed in 0..10 {
+        let mut enc = RobustEncoder::with_seed(
+            buf.clone(),
+            chunk_len,
+            EncoderType::Random,
+            0.2,
+            None,
+            0.05,
+            seed,
+        );
+        let mut dec = Decoder::with_mode(len, chunk_len, DecoderMode::Elimination);
+        cnt += run(&mut enc, &mut dec, 0.0, &buf);
+    }
+    // 200 blocks. A block no droplet covers costs about 40 more, which
+    // happens for a few of the seeds
+    assert!(cnt <= 2250, "needed {} for 10 objects", cnt);
+}
+
+#[test]
+fn elimination_reports_solved_blocks() {
+    let buf = random_data(10_000);
+    let mut enc = RobustEncoder::new(buf.clone(), 50, EncoderType::Random, 0.2, None, 0.05);
+    let mut dec = Decoder::with_mode(10_000, 50, DecoderMode::Elimination);
+    loop {
+        match dec.catch(enc.next()).unwrap() {
+            CatchResult::Missing(stats) => {
+                assert_eq!(stats.cnt_eliminated, 0);
+                assert_eq!(stats.cnt_peeled + stats.unknown_chunks, stats.cnt_chunks);
+            }
+            CatchResult::Finished(data, stats) => {
+                assert_eq!(buf, data);
+                assert_eq!(stats.cnt_peeled + stats.cnt_eliminated, 200);
+                println!("Finished, stats: {:?}", stats);
+                return;
+            }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+        }
+    }
+}
+
+#[test]
+fn elimination_peeling_mode_never_eliminates() {
+    let buf = random_data(5_000);
+    let mut enc = IdealEncoder::new(buf.clone(), 50, EncoderType::Random);
+    let mut dec = Decoder::with_mode(5_000, 50, DecoderMode::Peeling);
+    loop {
+        if let CatchResult::Finished(data, stats) = dec.catch(enc.next()).unwrap() {
+            assert_eq!(buf, data);
+            assert_eq!(stats.cnt_peeled, 100);
+            assert_eq!(stats.cnt_eliminated, 0);
+            return;
+        }
+    }
+}
+
+#[test]
+fn elimination_online() {
+    for _ in 0..10 {
+        let buf = random_data(10_000);
+        let mut enc = OnlineEncoder::new(buf.clone(), 10, EncoderType::Random, 0.01, 3);
+        let mut dec = OnlineDecoder::with_mode(
+            10_000,
+            10,
+            0.01,
+            3,
+            enc.outer_seed(),
+            DecoderMode::Elimination,
+        );
+        loop {
+            if let CatchResult::Finished(data, stats) = dec.catch(enc.next()).unwrap() {
+                assert_eq!(buf, data);
+                assert_eq!(stats.cnt_peeled + stats.cnt_eliminated, 1000);
+                break;
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn elimination_prop_test(total_len in 1usize..8192, chunk_len in 8usize..512, loss in 0.0f32..0.5) {
+        let buf = random_data(total_len);
+        let mut enc = RobustEncoder::new(buf.clone(), chunk_len, EncoderType::Systematic, 0.2, None, 0.05);
+        let mut dec = Decoder::with_mode(total_len, chunk_len, DecoderMode::Elimination);
+        run(&mut enc, &mut dec, loss, &buf);
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(50))]
+    #[test]
+    fn repeated_droplets_are_harmless(
+        seed in any::<u64>(),
+        elimination in any::<bool>(),
+        cnt_repeats in 1usize..4,
+    ) {
+        let mode = if elimination {
+            DecoderMode::Elimination
+        } else {
+            DecoderMode::Peeling
+        };
+        let buf = random_data(3_000);
+        let mut enc = RobustEncoder::with_seed(buf.clone(), 30, EncoderType::Random, 0.1, None, 0.5, seed);
+        let mut dec = Decoder::with_mode(buf.len(), 30, mode);
+        'decode: loop {
+            let drop = enc.next();
+            // copies of a droplet, e.g. retransmissions, carry no new information
+            for _ in 0..cnt_repeats {
+                match dec.catch(drop.clone()).unwrap() {
+                    CatchResult::Missing(_) => {}
+                    CatchResult::Finished(data, stats) => {
+                        prop_assert_eq!(data, buf);
+                        prop_assert_eq!(stats.cnt_peeled + stats.cnt_eliminated, stats.cnt_chunks);
+                        break 'decode;
+                    }
+                    CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+                }
+            }
+        }
+    }
+}
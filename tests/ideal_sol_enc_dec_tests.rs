@@ -18,6 +18,8 @@ fn enc_dec_helper(total_len: usize, chunk_len: usize, loss: f32, enc_type: Encod
     let len = buf.len();
     let to_compare = buf.clone();
 
+    // a systematic encoder and no loss on channel should only need k symbols
+    let lossless = loss == 0.0 && matches!(enc_type, EncoderType::Systematic);
     let mut enc = IdealEncoder::new(buf, chunk_len, enc_type);
     let mut dec = Decoder::new(len, chunk_len);
 
@@ -27,20 +29,25 @@ fn enc_dec_helper(total_len: usize, chunk_len: usize, loss: f32, enc_type: Encod
         let drop = enc.next();
 
         if loss_rng.gen::<f32>() > loss {
-            match dec.catch(drop) {
+            match dec.catch(drop).unwrap() {
                 CatchResult::Missing(stats) => {
-                    //a systematic encoder and no loss on channel should only need k symbols
-                    //assert_eq!(stats.cnt_chunks-stats.unknown_chunks, cnt_drops)
+                    if lossless {
+                        assert_eq!(stats.cnt_chunks - stats.unknown_chunks, stats.cnt_droplets);
+                    }
                     println!("Missing blocks {:?}", stats);
                 }
                 CatchResult::Finished(data, stats) => {
                     println!("Finished, stats: {:?}", stats);
+                    if lossless {
+                        assert_eq!(stats.cnt_droplets, stats.cnt_chunks);
+                    }
                     assert_eq!(to_compare.len(), data.len());
                     for i in 0..len {
                         assert_eq!(to_compare[i], data[i]);
                     }
                     return;
                 }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
             }
         }
     }
@@ -89,3 +96,28 @@ fn ideal_combinations_encode_decode_with_loss_begin_with_systematic() {
         }
     }
 }
+
+#[test]
+fn ideal_systematic_lossless_needs_k_droplets() {
+    let buf: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+    let to_compare = buf.clone();
+    let mut enc = IdealEncoder::new(buf, 100, EncoderType::Systematic);
+    let mut dec = Decoder::new(to_compare.len(), 100);
+    for i in 0..100 {
+        match dec.catch(enc.next()).unwrap() {
+            CatchResult::Missing(stats) => assert_eq!(stats.unknown_chunks, 100 - i - 1),
+            CatchResult::Finished(data, stats) => {
+                assert_eq!(i, 99);
+                assert_eq!(stats.cnt_droplets, 100);
+                assert_eq!(data, to_compare);
+            }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+        }
+    }
+    assert!(dec.is_finished());
+
+    // the repair droplets follow, no source block is sent twice
+    for _ in 0..100 {
+        assert!(matches!(enc.next().droptype, DropType::Seeded(_, _)));
+    }
+}
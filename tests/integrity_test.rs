@@ -0,0 +1,160 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::{DecodeError, Decoder};
+use self::fountaincode::digest::digest;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::storage::SeekStore;
+use self::fountaincode::types::*;
+use self::fountaincode::wire::{ObjectParams, WireError, CHECKSUM_LEN, HEADER_LEN};
+use proptest::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::io::Cursor;
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+#[test]
+fn checksum_roundtrip() {
+    let params = ObjectParams::new(1000, 64).with_object_id(9);
+    let drop = Droplet::new(DropType::Seeded(0xdead_beef, 5), vec![42; 64]);
+    let bytes = drop.to_bytes_with_checksum(&params);
+    assert_eq!(bytes.len(), HEADER_LEN + CHECKSUM_LEN + 64);
+
+    let (parsed, parsed_params) = Droplet::from_bytes(&bytes).unwrap();
+    assert_eq!(drop, parsed);
+    assert_eq!(params, parsed_params);
+}
+
+#[test]
+fn checksum_detects_corrupt_payload() {
+    let params = ObjectParams::new(1000, 64);
+    let mut bytes = Droplet::new(DropType::Edges(3), vec![7; 64]).to_bytes_with_checksum(&params);
+    let last = bytes.len() - 1;
+    bytes[last] ^= 0x10;
+    assert_eq!(
+        Droplet::from_bytes(&bytes),
+        Err(WireError::ChecksumMismatch)
+    );
+}
+
+#[test]
+fn decoder_rejects_corrupt_packets() {
+    let buf = random_data(10_000);
+    let to_compare = buf.clone();
+
+    let params = ObjectParams::new(buf.len(), 100);
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Random);
+    let mut dec = Decoder::new(params.len, params.blocksize);
+    let mut cnt_corrupted = 0;
+
+    loop {
+        let mut packet = enc.next().to_bytes_with_checksum(&params);
+        if thread_rng().gen::<f32>() < 0.2 {
+            let pos = thread_rng().gen_range(HEADER_LEN + CHECKSUM_LEN, packet.len());
+            packet[pos] ^= 1 << thread_rng().gen_range(0, 8);
+            cnt_corrupted += 1;
+            assert!(matches!(
+                dec.catch_packet(&packet),
+                Err(DecodeError::Wire(WireError::ChecksumMismatch))
+            ));
+            continue;
+        }
+        match dec.catch_packet(&packet).unwrap() {
+            CatchResult::Missing(stats) => assert_eq!(stats.cnt_corrupt, cnt_corrupted),
+            CatchResult::Finished(data, stats) => {
+                assert_eq!(stats.cnt_corrupt, cnt_corrupted);
+                assert_eq!(to_compare, data);
+                return;
+            }
+            CatchResult::DigestMismatch(_) => panic!("no digest was set"),
+        }
+    }
+}
+
+#[test]
+fn decoder_rejects_packets_of_other_objects() {
+    let mut dec = Decoder::new(1000, 100);
+    let packet =
+        Droplet::new(DropType::Edges(0), vec![0; 50]).to_bytes(&ObjectParams::new(1000, 50));
+    assert!(matches!(
+        dec.catch_packet(&packet),
+        Err(DecodeError::ParameterMismatch)
+    ));
+}
+
+#[test]
+fn digest_matches() {
+    let buf = random_data(5000);
+    let to_compare = buf.clone();
+
+    let mut dec = Decoder::new(buf.len(), 64);
+    dec.set_digest(digest(&buf));
+    let mut enc = IdealEncoder::new(buf, 64, EncoderType::Random);
+    loop {
+        match dec.catch(enc.next()).unwrap() {
+            CatchResult::Missing(_) => {}
+            CatchResult::Finished(data, _) => {
+                assert_eq!(to_compare, data);
+                break;
+            }
+            CatchResult::DigestMismatch(_) => panic!("the digest is correct"),
+        }
+    }
+    assert!(!dec.is_corrupt());
+}
+
+#[test]
+fn digest_mismatch_withholds_the_data() {
+    let buf = random_data(5000);
+    let len = buf.len();
+
+    // a corrupted droplet without checksum slips through
+    let mut dec = Decoder::new(len, 64);
+    dec.set_digest(digest(&buf));
+    let mut enc = IdealEncoder::new(buf, 64, EncoderType::Systematic);
+    let mut first = enc.next();
+    first.data[0] ^= 0xff;
+    dec.catch(first).unwrap();
+    loop {
+        match dec.catch(enc.next()).unwrap() {
+            CatchResult::Missing(_) => {}
+            CatchResult::Finished(_, _) => panic!("the object is corrupt"),
+            CatchResult::DigestMismatch(stats) => {
+                assert_eq!(stats.unknown_chunks, 0);
+                break;
+            }
+        }
+    }
+    assert!(dec.is_corrupt());
+}
+
+#[test]
+fn digest_is_checked_in_the_store() {
+    let buf = random_data(3000);
+    let store = SeekStore::new(Cursor::new(Vec::new()));
+    let mut dec = Decoder::with_store(buf.len(), 100, DecoderMode::Peeling, store);
+    dec.set_digest(digest(b"something else"));
+
+    let mut enc = IdealEncoder::new(buf, 100, EncoderType::Systematic);
+    while !dec.is_finished() {
+        dec.try_catch(enc.next()).unwrap();
+    }
+    assert!(dec.is_corrupt());
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10000))]
+    #[test]
+    fn checksum_bit_flip_prop_test(seed in any::<u64>(), pos in 0..HEADER_LEN + CHECKSUM_LEN + 32, bit in 0..8u8) {
+        let params = ObjectParams::new(500, 32);
+        let mut bytes = Droplet::new(DropType::Seeded(seed, 4), vec![0x5a; 32]).to_bytes_with_checksum(&params);
+        bytes[pos] ^= 1 << bit;
+        prop_assert!(Droplet::from_bytes(&bytes).is_err());
+    }
+}
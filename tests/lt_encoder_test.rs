@@ -0,0 +1,121 @@
+extern crate fountaincode;
+
+use self::fountaincode::custom_distribution::CustomDistribution;
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::ideal_soliton::IdealSoliton;
+use self::fountaincode::lt_encoder::LtEncoder;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::robust_soliton::RobustSoliton;
+use self::fountaincode::types::*;
+use std::io::Cursor;
+
+fn data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 7) as u8).collect()
+}
+
+#[test]
+fn builder_matches_the_wrappers() {
+    let mut lt = LtEncoder::builder(64)
+        .encodertype(EncoderType::Systematic)
+        .seed(5)
+        .build(data(3000));
+    let mut ideal = IdealEncoder::with_seed(data(3000), 64, EncoderType::Systematic, 5);
+    for _ in 0..200 {
+        assert_eq!(lt.next(), ideal.next());
+    }
+
+    let mut lt = LtEncoder::builder(64)
+        .seed(5)
+        .distribution(|k, seed| RobustSoliton::new(k, seed, 0.1, None, 0.05))
+        .build(data(3000));
+    let mut robust =
+        RobustEncoder::with_seed(data(3000), 64, EncoderType::Random, 0.1, None, 0.05, 5);
+    for _ in 0..200 {
+        assert_eq!(lt.next(), robust.next());
+    }
+}
+
+#[test]
+fn builder_from_reader() {
+    let buf = data(5000);
+    let mut mem = LtEncoder::builder(100).seed(9).build(buf.clone());
+    let mut reader = LtEncoder::builder(100)
+        .seed(9)
+        .build_from_reader(Cursor::new(buf))
+        .unwrap();
+    assert_eq!(reader.len(), 5000);
+    assert_eq!(reader.cnt_blocks(), 50);
+    assert_eq!(reader.blocksize(), 100);
+    for _ in 0..100 {
+        assert_eq!(mem.next(), reader.try_next().unwrap());
+    }
+}
+
+#[test]
+fn builder_with_custom_distribution_decodes() {
+    let buf = data(8000);
+    let mut enc = LtEncoder::builder(100)
+        .encodertype(EncoderType::Systematic)
+        .distribution(|_, seed| CustomDistribution::raptor(seed))
+        .build(buf.clone());
+    let mut dec = Decoder::with_mode(buf.len(), 100, DecoderMode::Elimination);
+    loop {
+        match dec.catch(enc.next()).unwrap() {
+            CatchResult::Missing(_) => {}
+            CatchResult::Finished(data, _) => {
+                assert_eq!(data, buf);
+                break;
+            }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+        }
+    }
+}
+
+#[test]
+fn wrappers_are_lt_encoders() {
+    let mut enc: LtEncoder<IdealSoliton> =
+        IdealEncoder::with_seed(data(1000), 100, EncoderType::Random, 3);
+    assert_eq!(enc.seed(), 3);
+    enc.next();
+    assert_eq!(enc.cnt, 1);
+    enc.seek(10);
+    assert_eq!(enc.cnt, 10);
+}
+
+#[test]
+fn encode_range_matches_next() {
+    for encodertype in [EncoderType::Systematic, EncoderType::Random].iter() {
+        let new_encoder = || {
+            LtEncoder::builder(64)
+                .encodertype(encodertype.clone())
+                .seed(9)
+                .distribution(|k, seed| RobustSoliton::new(k, seed, 0.1, None, 0.05))
+                .build(data(3001))
+        };
+        let mut enc = new_encoder();
+        let sequential: Vec<_> = (0..150).map(|_| enc.next()).collect();
+
+        let mut enc = new_encoder();
+        assert_eq!(enc.encode_range(0..150).unwrap(), sequential);
+        assert_eq!(enc.encode_range(30..120).unwrap(), &sequential[30..120]);
+        // the position of next does not move
+        assert_eq!(enc.next(), sequential[0]);
+
+        let mut enc = LtEncoder::builder(64)
+            .encodertype(encodertype.clone())
+            .seed(9)
+            .distribution(|k, seed| RobustSoliton::new(k, seed, 0.1, None, 0.05))
+            .build_from_reader(Cursor::new(data(3001)))
+            .unwrap();
+        assert_eq!(enc.encode_range(40..60).unwrap(), &sequential[40..60]);
+
+        #[cfg(feature = "parallel")]
+        {
+            let enc = new_encoder();
+            assert_eq!(enc.par_encode_range(0..150), sequential);
+            assert_eq!(enc.par_encode_range(75..76), &sequential[75..76]);
+        }
+    }
+}
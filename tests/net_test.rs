@@ -0,0 +1,244 @@
+#![cfg(feature = "net")]
+
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::auth::AuthKey;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::net::*;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::types::*;
+use self::fountaincode::wire::{Limits, ObjectParams};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::io;
+use std::net::UdpSocket;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+fn loopback() -> UdpSocket {
+    UdpSocket::bind("127.0.0.1:0").unwrap()
+}
+
+#[test]
+fn net_transfer_over_loopback() {
+    let buf = random_data(20_000);
+    let to_compare = buf.clone();
+
+    let rx_socket = loopback();
+    let rx_addr = rx_socket.local_addr().unwrap();
+    let receiver = Receiver::new(
+        rx_socket,
+        Duration::from_secs(5),
+        Duration::from_millis(100),
+    );
+    let handle = thread::spawn(move || receiver.receive());
+
+    let params = ObjectParams::new(buf.len(), 500).with_object_id(3);
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Systematic);
+    let sender = Sender::new(loopback(), rx_addr, params, 10_000);
+    let report = sender.send(&mut enc, 100_000).unwrap();
+
+    let (rx_params, data, _stats) = handle.join().unwrap().unwrap();
+    assert!(report.acknowledged);
+    assert_eq!(params, rx_params);
+    assert_eq!(to_compare, data);
+}
+
+#[test]
+fn net_transfer_with_packet_loss() {
+    let buf = random_data(50_000);
+    let to_compare = buf.clone();
+
+    // the acknowledgements get lost too, the receiver repeats them
+    let rx_socket = loopback();
+    let rx_addr = rx_socket.local_addr().unwrap();
+    let receiver = Receiver::new(
+        LossySocket::new(rx_socket, 0.5, 1),
+        Duration::from_secs(5),
+        Duration::from_millis(200),
+    );
+    let handle = thread::spawn(move || receiver.receive());
+
+    let params = ObjectParams::new(buf.len(), 500);
+    let mut enc = RobustEncoder::new(buf, params.blocksize, EncoderType::Random, 0.2, None, 0.05);
+    let sender = Sender::new(
+        LossySocket::new(loopback(), 0.3, 2),
+        rx_addr,
+        params,
+        20_000,
+    );
+    let report = sender.send(&mut enc, 100_000).unwrap();
+
+    let (_, data, stats) = handle.join().unwrap().unwrap();
+    assert!(report.acknowledged);
+    assert!(report.cnt_sent > stats.cnt_droplets);
+    assert_eq!(to_compare, data);
+}
+
+#[test]
+fn net_sender_stops_after_budget() {
+    let rx_socket = loopback();
+    let rx_addr = rx_socket.local_addr().unwrap();
+
+    let buf = random_data(10_000);
+    let params = ObjectParams::new(buf.len(), 100);
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Random);
+    let sender = Sender::new(loopback(), rx_addr, params, 0);
+    let report = sender.send(&mut enc, 10).unwrap();
+
+    assert_eq!(report.cnt_sent, 10);
+    assert!(!report.acknowledged);
+}
+
+#[test]
+fn net_receiver_times_out() {
+    let receiver = Receiver::new(
+        loopback(),
+        Duration::from_millis(50),
+        Duration::from_millis(0),
+    );
+    let err = receiver.receive().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+}
+
+#[test]
+fn net_receiver_ignores_forged_packets() {
+    let buf = random_data(20_000);
+    let to_compare = buf.clone();
+    let key = AuthKey::new(b"transfer key");
+
+    let rx_socket = loopback();
+    let rx_addr = rx_socket.local_addr().unwrap();
+    let mut receiver = Receiver::new(
+        rx_socket,
+        Duration::from_secs(5),
+        Duration::from_millis(100),
+    );
+    receiver.set_auth_key(key.clone());
+    let handle = thread::spawn(move || receiver.receive());
+
+    // the forger knows the object parameters, but not the key
+    let params = ObjectParams::new(buf.len(), 500);
+    let forger = loopback();
+    for idx in 0..params.cnt_blocks() {
+        let forged = Droplet::new(DropType::Edges(idx), vec![0; params.blocksize]);
+        forger.send_to(&forged.to_bytes(&params), rx_addr).unwrap();
+    }
+
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Systematic);
+    let mut sender = Sender::new(loopback(), rx_addr, params, 10_000);
+    sender.set_auth_key(key);
+    let report = sender.send(&mut enc, 100_000).unwrap();
+
+    let (_, data, _) = handle.join().unwrap().unwrap();
+    assert!(report.acknowledged);
+    assert_eq!(to_compare, data);
+}
+
+#[test]
+fn net_receiver_ignores_oversized_objects() {
+    let buf = random_data(20_000);
+    let to_compare = buf.clone();
+
+    let rx_socket = loopback();
+    let rx_addr = rx_socket.local_addr().unwrap();
+    let mut receiver = Receiver::new(
+        rx_socket,
+        Duration::from_secs(5),
+        Duration::from_millis(100),
+    );
+    receiver.set_limits(Limits {
+        max_object_size: 100_000,
+        max_blocks: 1000,
+        ..Limits::default()
+    });
+    let handle = thread::spawn(move || receiver.receive());
+
+    // too large, too large and too many blocks: the receiver neither allocates
+    // these objects nor locks onto them
+    let forger = loopback();
+    for &(len, blocksize) in &[(1 << 50, 16), (200_000, 16), (20_000, 16)] {
+        let packet = Droplet::new(DropType::Seeded(1, 1), vec![0; blocksize])
+            .to_bytes(&ObjectParams::new(len, blocksize).with_object_id(9));
+        forger.send_to(&packet, rx_addr).unwrap();
+    }
+
+    let params = ObjectParams::new(buf.len(), 500);
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Systematic);
+    let sender = Sender::new(loopback(), rx_addr, params, 10_000);
+    let report = sender.send(&mut enc, 100_000).unwrap();
+
+    let (rx_params, data, _) = handle.join().unwrap().unwrap();
+    assert!(report.acknowledged);
+    assert_eq!(params, rx_params);
+    assert_eq!(to_compare, data);
+}
+
+#[test]
+fn net_sender_ignores_unauthenticated_acks() {
+    let key = AuthKey::new(b"transfer key");
+    let rx_socket = loopback();
+    let rx_addr = rx_socket.local_addr().unwrap();
+    let tx_socket = loopback();
+    let tx_addr = tx_socket.local_addr().unwrap();
+
+    // a forger who spoofs the receiver address acknowledges the object
+    // without the key
+    let params = ObjectParams::new(10_000, 100).with_object_id(5);
+    let mut forged = b"FNTNDONE".to_vec();
+    forged.extend_from_slice(&params.object_id.to_be_bytes());
+    rx_socket.send_to(&forged, tx_addr).unwrap();
+
+    let mut enc = IdealEncoder::new(random_data(10_000), 100, EncoderType::Random);
+    let mut sender = Sender::new(tx_socket, rx_addr, params, 0);
+    sender.set_auth_key(key);
+    let report = sender.send(&mut enc, 50).unwrap();
+    assert_eq!(report.cnt_sent, 50);
+    assert!(!report.acknowledged);
+}
+
+#[test]
+fn net_receiver_is_not_kept_alive_by_junk() {
+    let rx_socket = loopback();
+    let rx_addr = rx_socket.local_addr().unwrap();
+    let receiver = Receiver::new(
+        rx_socket,
+        Duration::from_millis(300),
+        Duration::from_millis(300),
+    );
+
+    // junk arrives more often than the timeouts, before and after the object
+    let junk = thread::spawn(move || {
+        let socket = loopback();
+        let start = Instant::now();
+        while start.elapsed() < Duration::from_secs(3) {
+            socket.send_to(b"junk", rx_addr).unwrap();
+            thread::sleep(Duration::from_millis(20));
+        }
+    });
+
+    let start = Instant::now();
+    let err = receiver.receive().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(start.elapsed() < Duration::from_secs(2));
+
+    let handle = thread::spawn(move || receiver.receive());
+    let buf = random_data(10_000);
+    let params = ObjectParams::new(buf.len(), 500);
+    let mut enc = IdealEncoder::new(buf.clone(), params.blocksize, EncoderType::Systematic);
+    let sender = Sender::new(loopback(), rx_addr, params, 0);
+    let start = Instant::now();
+    assert!(sender.send(&mut enc, 100_000).unwrap().acknowledged);
+    // the receiver lingers for 300ms after the last droplet, not until the junk stops
+    let (_, data, _) = handle.join().unwrap().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(2));
+    assert_eq!(buf, data);
+    junk.join().unwrap();
+}
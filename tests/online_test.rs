@@ -0,0 +1,155 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::online_decoder::OnlineDecoder;
+use self::fountaincode::online_encoder::{cnt_aux_blocks, OnlineEncoder};
+use self::fountaincode::online_soliton::OnlineSoliton;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::soliton::Soliton;
+use self::fountaincode::types::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+fn run<E: Encoder>(enc: &mut E, mut catch: impl FnMut(Droplet) -> CatchResult) -> usize {
+    loop {
+        if let CatchResult::Finished(_, stats) = catch(enc.next()) {
+            return stats.cnt_droplets;
+        }
+    }
+}
+
+fn enc_dec_helper(total_len: usize, chunk_len: usize, loss: f32, enc_type: EncoderType) {
+    let buf = random_data(total_len);
+    let to_compare = buf.clone();
+
+    let mut enc = OnlineEncoder::new(buf, chunk_len, enc_type, 0.01, 3);
+    let mut dec = OnlineDecoder::new(total_len, chunk_len, 0.01, 3, enc.outer_seed());
+
+    let mut loss_rng = thread_rng();
+
+    loop {
+        let drop = enc.next();
+        if loss_rng.gen::<f32>() > loss {
+            match dec.catch(drop).unwrap() {
+                CatchResult::Missing(stats) => {
+                    assert!(stats.unknown_chunks <= stats.cnt_chunks);
+                }
+                CatchResult::Finished(data, stats) => {
+                    println!("Finished, stats: {:?}", stats);
+                    assert_eq!(to_compare, data);
+                    return;
+                }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+            }
+        }
+    }
+}
+
+#[test]
+fn online_soliton_has_constant_average_degree() {
+    let mut sol = OnlineSoliton::new(0.01, thread_rng().gen::<u64>());
+    assert_eq!(sol.max_degree(), 2115);
+
+    let cnt = 100_000;
+    let sum: usize = (0..cnt).map(|_| sol.next()).sum();
+    let avg = sum as f64 / cnt as f64;
+    assert!(avg > 6.0 && avg < 10.0, "average degree {}", avg);
+}
+
+#[test]
+fn online_aux_blocks() {
+    assert_eq!(cnt_aux_blocks(1000, 0.01, 3), 17);
+    assert_eq!(cnt_aux_blocks(1, 0.01, 3), 1);
+}
+
+#[test]
+fn online_enc_dec_simple() {
+    enc_dec_helper(1_024, 512, 0.0, EncoderType::Systematic);
+    enc_dec_helper(1_024, 512, 0.0, EncoderType::Random);
+}
+
+#[test]
+fn online_enc_dec_uneven_sizes() {
+    for size in 1000..1020 {
+        for chunk in 10..20 {
+            enc_dec_helper(size, chunk, 0.0, EncoderType::Systematic);
+            enc_dec_helper(size, chunk, 0.0, EncoderType::Random);
+        }
+    }
+}
+
+#[test]
+fn online_enc_dec_with_loss() {
+    for size in &[1, 100, 1000, 5000] {
+        for loss in &[0.1, 0.3, 0.5, 0.9] {
+            enc_dec_helper(*size, 16, *loss, EncoderType::Systematic);
+            enc_dec_helper(*size, 16, *loss, EncoderType::Random);
+        }
+    }
+}
+
+#[test]
+fn online_systematic_lossless_needs_k_droplets() {
+    let buf = random_data(1000);
+    let mut enc = OnlineEncoder::new(buf, 10, EncoderType::Systematic, 0.01, 3);
+    let mut dec = OnlineDecoder::new(1000, 10, 0.01, 3, enc.outer_seed());
+    assert_eq!(run(&mut enc, |drop| dec.catch(drop).unwrap()), 100);
+}
+
+#[test]
+fn online_overhead_compared_to_robust() {
+    let len = 100_000;
+    let chunk_len = 50;
+    let rounds = 5;
+    let mut online = 0;
+    let mut robust = 0;
+    for _ in 0..rounds {
+        let buf = random_data(len);
+
+        let mut enc = OnlineEncoder::new(buf.clone(), chunk_len, EncoderType::Random, 0.01, 3);
+        let mut dec = OnlineDecoder::new(len, chunk_len, 0.01, 3, enc.outer_seed());
+        online += run(&mut enc, |drop| dec.catch(drop).unwrap());
+
+        let mut enc = RobustEncoder::new(buf, chunk_len, EncoderType::Random, 0.2, None, 0.05);
+        let mut dec = Decoder::new(len, chunk_len);
+        robust += run(&mut enc, |drop| dec.catch(drop).unwrap());
+    }
+    println!("online: {}, robust: {}", online / rounds, robust / rounds);
+    // (1 + epsilon) * (1 + 0.55 * q * epsilon) * n droplets suffice asymptotically,
+    // leave some slack for 2000 blocks
+    assert!(
+        online / rounds < 2000 * 13 / 10,
+        "online needed {}",
+        online / rounds
+    );
+}
+
+#[test]
+fn online_same_seed_same_stream() {
+    let data = random_data(5000);
+    for enc_type in &[EncoderType::Systematic, EncoderType::Random] {
+        let mut a = OnlineEncoder::with_seed(data.clone(), 50, enc_type.clone(), 0.01, 3, 42);
+        let mut b = OnlineEncoder::with_seed(data.clone(), 50, enc_type.clone(), 0.01, 3, 42);
+        let mut c = OnlineEncoder::with_seed(data.clone(), 50, enc_type.clone(), 0.01, 3, 43);
+        assert_eq!(a.outer_seed(), b.outer_seed());
+        let stream_a: Vec<_> = (0..300).map(|_| a.next()).collect();
+        let stream_b: Vec<_> = (0..300).map(|_| b.next()).collect();
+        let stream_c: Vec<_> = (0..300).map(|_| c.next()).collect();
+        assert_eq!(stream_a, stream_b);
+        assert_ne!(stream_a, stream_c);
+    }
+}
+
+#[test]
+#[should_panic(expected = "empty object")]
+fn online_empty_object_is_rejected() {
+    OnlineEncoder::new(Vec::new(), 10, EncoderType::Systematic, 0.01, 3);
+}
@@ -0,0 +1,116 @@
+extern crate fountaincode;
+
+use self::fountaincode::custom_distribution::{CustomDistribution, DistributionError};
+use self::fountaincode::ideal_soliton::IdealSoliton;
+use self::fountaincode::optimizer::*;
+use self::fountaincode::types::DecoderMode;
+
+#[test]
+fn preset_roundtrip() {
+    let presets = [
+        Preset::Robust(RobustParams {
+            c: 0.05,
+            delta: 0.5,
+            spike: None,
+        }),
+        Preset::Robust(RobustParams {
+            c: 0.2,
+            delta: 0.05,
+            spike: Some(40),
+        }),
+        Preset::Custom(vec![0.0, 0.1, 0.5, 0.4]),
+    ];
+    for preset in presets.iter() {
+        let line = preset.to_string();
+        assert_eq!(&line.parse::<Preset>().unwrap(), preset, "{}", line);
+    }
+    assert_eq!(
+        "robust c=0.05 delta=0.5 spike=auto".parse::<Preset>(),
+        Ok(presets[0].clone())
+    );
+}
+
+#[test]
+fn preset_rejects_invalid_lines() {
+    for line in [
+        "",
+        "ideal",
+        "robust c=0.1",
+        "robust c=x delta=0.1",
+        "robust c=0.1 delta=0.1 eps=1",
+        "custom 0 a",
+    ]
+    .iter()
+    {
+        assert!(
+            matches!(line.parse::<Preset>(), Err(PresetError::Syntax(_))),
+            "{}",
+            line
+        );
+    }
+    assert_eq!(
+        "custom 0 0.5".parse::<Preset>(),
+        Err(PresetError::Distribution(DistributionError::NotNormalized(
+            0.5
+        )))
+    );
+}
+
+#[test]
+fn simulation_is_reproducible() {
+    let sim = Simulation::new(50, 0.2).with_trials(20).with_seed(7);
+    let params = RobustParams {
+        c: 0.1,
+        delta: 0.5,
+        spike: None,
+    };
+    let first = sim.run(|seed| params.soliton(50, seed));
+    let second = sim.run(|seed| params.soliton(50, seed));
+    assert_eq!(first, second);
+    assert_eq!(first.cnt_failed, 0);
+    // with a fifth of the droplets lost, at least a quarter more have to be sent
+    assert!(first.mean >= 0.25);
+    assert!(first.p95 >= first.mean);
+}
+
+#[test]
+fn simulation_counts_failed_trials() {
+    // the ideal soliton rarely gets past the first few blocks with peeling alone
+    let mut sim = Simulation::new(200, 0.0).with_trials(10);
+    sim.max_overhead = 0.0;
+    let overhead = sim.run(|seed| IdealSoliton::new(200, seed));
+    assert!(overhead.cnt_failed > 0);
+    assert!(overhead.p95 <= 0.0);
+}
+
+#[test]
+fn optimize_robust_sorts_the_candidates() {
+    let sim = Simulation::new(50, 0.0)
+        .with_trials(10)
+        .with_mode(DecoderMode::Elimination);
+    let candidates = optimize_robust(&sim, Objective::P95);
+    assert!(candidates.len() > 1);
+    for pair in candidates.windows(2) {
+        let a = &pair[0].overhead;
+        let b = &pair[1].overhead;
+        assert!((a.cnt_failed, a.p95) <= (b.cnt_failed, b.p95));
+    }
+}
+
+#[test]
+fn optimize_custom_does_not_get_worse() {
+    let sim = Simulation::new(30, 0.1).with_trials(10);
+    let sol = RobustParams {
+        c: 0.1,
+        delta: 0.5,
+        spike: None,
+    }
+    .soliton(30, 0);
+    let initial: Vec<f64> = (0..=30).map(|d| sol.pmf(d)).collect();
+    let start = sim.run(|seed| CustomDistribution::new(&initial, seed).unwrap());
+
+    let best = optimize_custom(&sim, Objective::Mean, &initial, 20).unwrap();
+    assert!(best.overhead.mean <= start.mean);
+    assert!((best.params.iter().sum::<f64>() - 1.0).abs() < 1e-9);
+    assert!(optimize_custom(&sim, Objective::Mean, &[0.0, 0.5], 1).is_err());
+}
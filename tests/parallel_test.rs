@@ -0,0 +1,116 @@
+#![cfg(feature = "parallel")]
+
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::digest::digest;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::lt_encoder::LtEncoder;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::robust_soliton::RobustSoliton;
+use self::fountaincode::storage::SeekStore;
+use self::fountaincode::types::*;
+use proptest::prelude::*;
+use rand::{thread_rng, Rng};
+use std::io::Cursor;
+
+fn random_data(len: usize) -> Vec<u8> {
+    (0..len).map(|_| thread_rng().gen()).collect()
+}
+
+/// Catches the droplets until the object is complete, returns the data and
+/// the statistics of the last droplet
+fn decode(dec: &mut Decoder, drops: &[Droplet]) -> (Vec<u8>, usize, usize, usize) {
+    for drop in drops {
+        if let CatchResult::Finished(data, stats) = dec.catch(drop.clone()).unwrap() {
+            return (
+                data,
+                stats.cnt_droplets,
+                stats.cnt_peeled,
+                stats.cnt_eliminated,
+            );
+        }
+    }
+    panic!("the droplets do not suffice");
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(50))]
+    #[test]
+    fn parallel_decoding_matches_sequential(
+        len in 1usize..20_000,
+        blocksize in 1usize..200,
+        seed in any::<u64>(),
+        systematic in any::<bool>(),
+        elimination in any::<bool>(),
+    ) {
+        let buf = random_data(len);
+        let encodertype = if systematic {
+            EncoderType::Systematic
+        } else {
+            EncoderType::Random
+        };
+        let mode = if elimination {
+            DecoderMode::Elimination
+        } else {
+            DecoderMode::Peeling
+        };
+        let k = len.div_ceil(blocksize);
+        let enc = LtEncoder::builder(blocksize)
+            .encodertype(encodertype)
+            .seed(seed)
+            .distribution(|k, seed| RobustSoliton::new(k, seed, 0.1, None, 0.5))
+            .build(buf.clone());
+        let drops = enc.par_encode_range(0..10 * k + 100);
+
+        let mut dec = Decoder::with_mode(len, blocksize, mode);
+        let sequential = decode(&mut dec, &drops);
+        let mut dec = Decoder::with_mode(len, blocksize, mode);
+        dec.set_parallel(true).unwrap();
+        let parallel = decode(&mut dec, &drops);
+        prop_assert_eq!(&parallel.0, &buf);
+        prop_assert_eq!(parallel, sequential);
+    }
+}
+
+#[test]
+fn parallel_decoding_to_a_store() {
+    let buf = random_data(50_000);
+    let mut enc = RobustEncoder::new(buf.clone(), 100, EncoderType::Random, 0.1, None, 0.5);
+    let store = SeekStore::new(Cursor::new(Vec::new()));
+    let mut dec = Decoder::with_store(buf.len(), 100, DecoderMode::Peeling, store);
+    dec.set_digest(digest(&buf));
+    dec.set_parallel(true).unwrap();
+    while !dec.is_finished() {
+        dec.try_catch(enc.next()).unwrap();
+    }
+    assert!(!dec.is_corrupt());
+    assert_eq!(dec.into_inner().into_inner().into_inner(), buf);
+}
+
+#[test]
+fn parallel_mode_changes_while_decoding() {
+    let buf = random_data(50_000);
+    let drops = RobustEncoder::with_seed(buf.clone(), 100, EncoderType::Random, 0.1, None, 0.5, 3)
+        .par_encode_range(0..2000);
+
+    // the blocks recovered in parallel are XORed out of the waiting droplets
+    // once the mode is turned off, and vice versa
+    let mut dec = Decoder::new(buf.len(), 100);
+    dec.set_parallel(true).unwrap();
+    let (first, rest) = drops.split_at(400);
+    for drop in first {
+        dec.catch(drop.clone()).unwrap();
+    }
+    assert!(dec.statistics().cnt_peeled > 0);
+    dec.set_parallel(false).unwrap();
+    let (second, rest) = rest.split_at(200);
+    for drop in second {
+        dec.catch(drop.clone()).unwrap();
+    }
+    dec.set_parallel(true).unwrap();
+    let (data, _, _, _) = decode(&mut dec, rest);
+    assert_eq!(data, buf);
+}
@@ -0,0 +1,69 @@
+extern crate fountaincode;
+
+use self::fountaincode::lt_encoder::get_sample_from_rng_by_seed;
+use self::fountaincode::prng::{splitmix64, Prng};
+
+// The vectors were computed with an independent implementation of the
+// algorithms described in `prng`, peers in other languages can check against them.
+
+#[test]
+fn splitmix64_vectors() {
+    let mut state = 0;
+    assert_eq!(splitmix64(&mut state), 0xe220_a839_7b1d_cdaf);
+    assert_eq!(splitmix64(&mut state), 0x6e78_9e6a_a1b9_65f4);
+    assert_eq!(splitmix64(&mut state), 0x06c4_5d18_8009_454f);
+}
+
+#[test]
+fn xoshiro_vectors() {
+    let mut rng = Prng::new(0);
+    let expected = [
+        0x99ec_5f36_cb75_f2b4,
+        0xbf6e_1f78_4956_452a,
+        0x1a5f_849d_4933_e6e0,
+        0x6aa5_94f1_262d_2d2c,
+    ];
+    for value in expected.iter() {
+        assert_eq!(rng.next_u64(), *value);
+    }
+
+    let mut rng = Prng::new(0x0123_4567_89ab_cdef);
+    let expected = [
+        0xa2c2_a420_38d4_ec3d,
+        0x05fc_25d0_738e_7b0f,
+        0x625e_7bff_938e_701e,
+        0x1ba4_ddc6_fe2b_5726,
+    ];
+    for value in expected.iter() {
+        assert_eq!(rng.next_u64(), *value);
+    }
+}
+
+#[test]
+fn below_and_f64_vectors() {
+    let mut rng = Prng::new(1);
+    let values: Vec<u64> = (0..8).map(|_| rng.below(10)).collect();
+    assert_eq!(values, vec![7, 2, 0, 3, 1, 2, 6, 9]);
+
+    assert_eq!(Prng::new(0).next_f64(), 0.601_262_999_417_904_8);
+}
+
+#[test]
+fn neighbour_vectors() {
+    let sample: Vec<usize> = get_sample_from_rng_by_seed(42, 100, 10).collect();
+    assert_eq!(sample, vec![2, 10, 65, 19, 56, 24, 77, 35, 22, 85]);
+
+    let sample: Vec<usize> = get_sample_from_rng_by_seed(0xdead_beef, 1_000_000, 5).collect();
+    assert_eq!(sample, vec![312_263, 637_046, 664_139, 766_317, 761_838]);
+
+    let sample: Vec<usize> = get_sample_from_rng_by_seed(7, 10, 12).collect();
+    assert_eq!(sample, (0..10).collect::<Vec<usize>>());
+}
+
+#[test]
+fn large_samples_are_distinct() {
+    let mut sample = Prng::new(3).sample(1000, 600);
+    sample.sort_unstable();
+    sample.dedup();
+    assert_eq!(sample.len(), 600);
+}
@@ -0,0 +1,266 @@
+extern crate fountaincode;
+extern crate rand;
+extern crate raptorq;
+
+use self::fountaincode::decoder::DecodeError;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::raptorq_decoder::RaptorQDecoder;
+use self::fountaincode::raptorq_encoder::RaptorQEncoder;
+use self::fountaincode::types::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use raptorq::{
+    extended_source_block_symbols, EncodingPacket, ObjectTransmissionInformation, PayloadId,
+    SourceBlockDecoder, SourceBlockEncoder,
+};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+fn enc_dec_helper(total_len: usize, chunk_len: usize, loss: f32, enc_type: EncoderType) {
+    let buf = random_data(total_len);
+    let to_compare = buf.clone();
+
+    let mut enc = RaptorQEncoder::new(buf, chunk_len, enc_type);
+    let mut dec = RaptorQDecoder::new(total_len, chunk_len);
+
+    let mut loss_rng = thread_rng();
+
+    loop {
+        let drop = enc.next();
+        if loss_rng.gen::<f32>() > loss {
+            match dec.catch(drop).unwrap() {
+                CatchResult::Missing(_stats) => {}
+                CatchResult::Finished(data, stats) => {
+                    println!("Finished, stats: {:?}", stats);
+                    assert_eq!(to_compare, data);
+                    return;
+                }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+            }
+        }
+    }
+}
+
+#[test]
+fn raptorq_systematic_lossless_needs_k_symbols() {
+    let buf = random_data(1000);
+    let to_compare = buf.clone();
+
+    let mut enc = RaptorQEncoder::new(buf, 100, EncoderType::Systematic);
+    let mut dec = RaptorQDecoder::new(1000, 100);
+
+    for _ in 0..9 {
+        match dec.catch(enc.next()).unwrap() {
+            CatchResult::Missing(stats) => assert!(stats.unknown_chunks > 0),
+            CatchResult::Finished(_, _) => panic!("finished too early"),
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+        }
+    }
+    match dec.catch(enc.next()).unwrap() {
+        CatchResult::Finished(data, stats) => {
+            assert_eq!(to_compare, data);
+            assert_eq!(stats.cnt_droplets, 10);
+        }
+        CatchResult::Missing(_) => panic!("10 source symbols must be enough"),
+        CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+    }
+    assert!(matches!(
+        dec.catch(enc.next()),
+        Err(DecodeError::AlreadyFinished)
+    ));
+}
+
+#[test]
+fn raptorq_rejects_foreign_droplets() {
+    let mut dec = RaptorQDecoder::new(1100, 100);
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Edges(0), vec![0; 100])),
+        Err(DecodeError::ParameterMismatch)
+    ));
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Esi(0), vec![0; 99])),
+        Err(DecodeError::PayloadLength {
+            expected: 100,
+            actual: 99
+        })
+    ));
+    // 11 source symbols are padded to 12, the ISI of the last id does not fit into 32 bits
+    assert!(matches!(
+        dec.catch(Droplet::new(DropType::Esi(u32::MAX), vec![0; 100])),
+        Err(DecodeError::EsiOutOfRange { esi: u32::MAX })
+    ));
+    match dec.catch(Droplet::new(DropType::Esi(0), vec![0; 100])) {
+        Ok(CatchResult::Missing(stats)) => assert_eq!(stats.cnt_droplets, 1),
+        _ => panic!("a valid symbol must be accepted"),
+    }
+}
+
+#[test]
+fn raptorq_encode_decode_with_uneven_sizes() {
+    for size in 1000..1020 {
+        for chunk in 10..20 {
+            enc_dec_helper(size, chunk, 0.0, EncoderType::Random);
+        }
+    }
+}
+
+#[test]
+fn raptorq_encode_decode_with_loss() {
+    for size in &[1, 100, 1000, 5000] {
+        for loss in &[0.1, 0.3, 0.5, 0.9] {
+            enc_dec_helper(*size, 16, *loss, EncoderType::Systematic);
+            enc_dec_helper(*size, 16, *loss, EncoderType::Random);
+        }
+    }
+}
+
+#[test]
+fn raptorq_repair_only_overhead_is_small() {
+    // RFC 6330 promises a failure probability below 1% with k symbols,
+    // and orders of magnitude less with a few more
+    let mut failures = 0;
+    for _ in 0..20 {
+        let buf = random_data(2000);
+        let mut enc = RaptorQEncoder::new(buf, 20, EncoderType::Random);
+        let mut dec = RaptorQDecoder::new(2000, 20);
+        let cnt = loop {
+            if let CatchResult::Finished(_, stats) = dec.catch(enc.next()).unwrap() {
+                break stats.cnt_droplets;
+            }
+        };
+        assert!(cnt <= 103, "needed {} symbols", cnt);
+        if cnt > 100 {
+            failures += 1;
+        }
+    }
+    assert!(
+        failures <= 2,
+        "{} of 20 decodings needed overhead",
+        failures
+    );
+}
+
+/// Pads the data to whole symbols, as the reference implementation expects
+fn reference_encoder(data: &[u8], symbol_size: usize) -> SourceBlockEncoder {
+    let mut padded = data.to_vec();
+    padded.resize(data.len().div_ceil(symbol_size) * symbol_size, 0);
+    let config = ObjectTransmissionInformation::new(0, symbol_size as u16, 0, 1, 1);
+    SourceBlockEncoder::new2(0, &config, &padded)
+}
+
+// The reference implementation labels repair packets with their internal symbol id,
+// K' + r instead of the K + r of section 5.3.1. The symbols themselves are identical.
+fn reference_esi(esi: u32, k: u32) -> u32 {
+    if esi < k {
+        esi
+    } else {
+        esi + extended_source_block_symbols(k) - k
+    }
+}
+
+#[test]
+fn raptorq_symbols_match_reference_implementation() {
+    for &(len, symbol_size) in &[(1000, 8), (1234, 16), (4096, 64), (20000, 40), (8, 8)] {
+        let data = random_data(len);
+        let k = len.div_ceil(symbol_size) as u32;
+        let reference = reference_encoder(&data, symbol_size);
+        let reference_packets: Vec<EncodingPacket> = reference
+            .source_packets()
+            .into_iter()
+            .chain(reference.repair_packets(0, 50))
+            .collect();
+
+        let mut enc = RaptorQEncoder::new(data, symbol_size, EncoderType::Systematic);
+        for packet in reference_packets.iter() {
+            let drop = enc.next();
+            let esi = match drop.droptype {
+                DropType::Esi(esi) => esi,
+                _ => unreachable!(),
+            };
+            assert_eq!(
+                reference_esi(esi, k),
+                packet.payload_id().encoding_symbol_id()
+            );
+            assert_eq!(packet.data(), &drop.data[..]);
+        }
+    }
+}
+
+#[test]
+fn raptorq_decode_reference_packets() {
+    let data = random_data(3000);
+    let symbol_size = 30;
+    let k = 100;
+    let reference = reference_encoder(&data, symbol_size);
+    let mut dec = RaptorQDecoder::new(data.len(), symbol_size);
+
+    // drop every other source symbol
+    let packets = reference
+        .source_packets()
+        .into_iter()
+        .step_by(2)
+        .chain(reference.repair_packets(0, 80));
+    for packet in packets {
+        let (payload_id, symbol) = packet.split();
+        let ref_esi = payload_id.encoding_symbol_id();
+        let esi = if ref_esi < k {
+            ref_esi
+        } else {
+            ref_esi - (extended_source_block_symbols(k) - k)
+        };
+        if let CatchResult::Finished(result, _) =
+            dec.catch(Droplet::new(DropType::Esi(esi), symbol)).unwrap()
+        {
+            assert_eq!(data, result);
+            return;
+        }
+    }
+    panic!("could not decode the reference packets");
+}
+
+#[test]
+fn raptorq_reference_decodes_our_symbols() {
+    let data = random_data(5000);
+    let symbol_size = 50;
+    let k = 100;
+    let mut enc = RaptorQEncoder::new(data.clone(), symbol_size, EncoderType::Random);
+    let mut dec = SourceBlockDecoder::new2(
+        0,
+        &ObjectTransmissionInformation::new(0, symbol_size as u16, 0, 1, 1),
+        data.len() as u64,
+    );
+
+    for _ in 0..110 {
+        let drop = enc.next();
+        let esi = match drop.droptype {
+            DropType::Esi(esi) => esi,
+            _ => unreachable!(),
+        };
+        let packet = EncodingPacket::new(PayloadId::new(0, reference_esi(esi, k)), drop.data);
+        if let Some(result) = dec.decode(vec![packet]) {
+            assert_eq!(data, result);
+            return;
+        }
+    }
+    panic!("reference implementation could not decode our symbols");
+}
+
+#[test]
+fn raptorq_large_source_block() {
+    // the constraint matrix is sparse, so thousands of symbols stay cheap
+    let k = 10000;
+    let data = random_data(k * 4);
+    let mut enc = RaptorQEncoder::new(data.clone(), 4, EncoderType::Random);
+    let mut dec = RaptorQDecoder::new(data.len(), 4);
+    for _ in 0..k + 10 {
+        if let CatchResult::Finished(result, _) = dec.catch(enc.next()).unwrap() {
+            assert_eq!(data, result);
+            return;
+        }
+    }
+    panic!("10 symbols of overhead were not enough");
+}
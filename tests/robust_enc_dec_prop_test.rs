@@ -48,11 +48,11 @@ proptest! {
 
         let losses = vec![0.1, 0.3, 0.5, 0.9];
 
-        let mut dec = Decoder::new(len, chunk_len as usize);
         let mut res: Vec<u8> = vec![];
 
         for loss in losses {
             let mut enc = RobustEncoder::new(buf.clone(), chunk_len as usize, EncoderType::Systematic, 0.2, None, 0.05);
+            let mut dec = Decoder::new(len, chunk_len as usize);
             let sw = Stopwatch::start_new();
             res = run_lossy(&mut enc, &mut dec, loss);
             println!("total_len: {:?}, chunk_len: {:?}, loss: {:?}, time: {:#?}", total_len, chunk_len, loss, sw.elapsed());
@@ -65,7 +65,7 @@ proptest! {
 fn run(enc: &mut RobustEncoder, dec: &mut Decoder) -> Vec<u8> {
     loop {
         let drop = enc.next();
-        match dec.catch(drop) {
+        match dec.catch(drop).unwrap() {
             CatchResult::Missing(_stats) => {
                 // println!("Missing blocks {:?}", stats);
             }
@@ -73,6 +73,7 @@ fn run(enc: &mut RobustEncoder, dec: &mut Decoder) -> Vec<u8> {
                 // println!("Finished, stats: {:?}", stats);
                 break data;
             }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
         }
     }
 }
@@ -82,7 +83,7 @@ fn run_lossy(enc: &mut RobustEncoder, dec: &mut Decoder, loss: f32) -> Vec<u8> {
     loop {
         if loss_rng.gen::<f32>() > loss {
             let drop = enc.next();
-            match dec.catch(drop) {
+            match dec.catch(drop).unwrap() {
                 CatchResult::Missing(_stats) => {
                     // println!("Missing blocks {:?}", stats);
                 }
@@ -90,6 +91,7 @@ fn run_lossy(enc: &mut RobustEncoder, dec: &mut Decoder, loss: f32) -> Vec<u8> {
                     println!("overhead: {:?}", stats.overhead);
                     break data;
                 }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
             }
         }
     }
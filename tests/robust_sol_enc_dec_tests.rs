@@ -26,6 +26,8 @@ fn enc_dec_helper(
     let len = buf.len();
     let to_compare = buf.clone();
 
+    // a systematic encoder and no loss on channel should only need k symbols
+    let lossless = loss == 0.0 && matches!(enc_type, EncoderType::Systematic);
     let mut enc = RobustEncoder::new(buf, chunk_len, enc_type, c, spike, delta);
     let mut dec = Decoder::new(len, chunk_len);
 
@@ -34,20 +36,25 @@ fn enc_dec_helper(
     loop {
         if loss_rng.gen::<f32>() > loss {
             let drop = enc.next();
-            match dec.catch(drop) {
+            match dec.catch(drop).unwrap() {
                 CatchResult::Missing(stats) => {
-                    //a systematic encoder and no loss on channel should only need k symbols
-                    //assert_eq!(stats.cnt_chunks-stats.unknown_chunks, cnt_drops)
+                    if lossless {
+                        assert_eq!(stats.cnt_chunks - stats.unknown_chunks, stats.cnt_droplets);
+                    }
                     println!("Missing blocks {:?}", stats);
                 }
                 CatchResult::Finished(data, stats) => {
                     println!("Finished, stats: {:?}", stats);
+                    if lossless {
+                        assert_eq!(stats.cnt_droplets, stats.cnt_chunks);
+                    }
                     assert_eq!(to_compare.len(), data.len());
                     for i in 0..len {
                         assert_eq!(to_compare[i], data[i]);
                     }
                     return;
                 }
+                CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
             }
         }
     }
@@ -124,7 +131,7 @@ fn robust_enc_dec_combination_systematic_lossy() {
 fn robust_enc_dec_combination_random_lossy() {
     for size in 1000..1100 {
         for chunk in 100..130 {
-            for loss in vec![0.1, 0.3, 0.5, 0.9] {
+            for loss in [0.1, 0.3, 0.5, 0.9] {
                 enc_dec_helper(size, chunk, loss, 0.2, None, 0.05, EncoderType::Random);
             }
         }
@@ -0,0 +1,66 @@
+extern crate fountaincode;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::lt_encoder::{get_sample_from_rng_by_seed, LtEncoder};
+use self::fountaincode::types::*;
+use proptest::prelude::*;
+use std::collections::HashSet;
+
+#[test]
+fn sample_is_uniform() {
+    // every block is a neighbour of 2000 * 5 / 50 = 200 droplets on average
+    let mut cnt = vec![0; 50];
+    for seed in 0..2000 {
+        for idx in get_sample_from_rng_by_seed(seed, 50, 5) {
+            cnt[idx] += 1;
+        }
+    }
+    assert!(cnt.iter().all(|c| (140..260).contains(c)), "{:?}", cnt);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(1000))]
+    #[test]
+    fn sample_is_distinct_prop_test(seed in any::<u64>(), cnt_blocks in 1..500usize, degree in 0..600usize) {
+        let sample: Vec<usize> = get_sample_from_rng_by_seed(seed, cnt_blocks, degree).collect();
+        prop_assert_eq!(sample.len(), degree.min(cnt_blocks));
+        prop_assert!(sample.iter().all(|idx| *idx < cnt_blocks));
+        let distinct: HashSet<&usize> = sample.iter().collect();
+        prop_assert_eq!(distinct.len(), sample.len());
+
+        let again: Vec<usize> = get_sample_from_rng_by_seed(seed, cnt_blocks, degree).collect();
+        prop_assert_eq!(sample, again);
+    }
+
+    #[test]
+    fn encoder_and_decoder_share_neighbours_prop_test(seed in any::<u64>(), cnt_blocks in 2..100usize, idx in 0..20usize) {
+        let blocksize = 8;
+        let data: Vec<u8> = (0..cnt_blocks * blocksize).map(|i| (i as u64 ^ seed) as u8).collect();
+        let mut enc = LtEncoder::builder(blocksize).seed(seed).build(data.clone());
+        enc.seek(idx);
+        let drop = enc.next();
+        let (drop_seed, degree) = match drop.droptype {
+            DropType::Seeded(drop_seed, degree) => (drop_seed, degree),
+            _ => unreachable!("a random encoder only sends seeded droplets"),
+        };
+        let neighbours: Vec<usize> = get_sample_from_rng_by_seed(drop_seed, cnt_blocks, degree).collect();
+        prop_assert_eq!(neighbours.len(), degree);
+
+        // the decoder learns one neighbour from the droplet only, which
+        // works if it derives the same neighbours as the encoder
+        let missing = neighbours[neighbours.len() - 1];
+        let mut dec = Decoder::new(data.len(), blocksize);
+        dec.catch(drop).unwrap();
+        let mut result = None;
+        for block in (0..cnt_blocks).filter(|block| *block != missing) {
+            let payload = data[block * blocksize..(block + 1) * blocksize].to_vec();
+            result = Some(dec.catch(Droplet::new(DropType::Edges(block), payload)).unwrap());
+        }
+        match result {
+            Some(CatchResult::Finished(decoded, _)) => prop_assert_eq!(decoded, data),
+            other => prop_assert!(false, "not finished: {:?}", other),
+        }
+    }
+}
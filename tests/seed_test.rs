@@ -0,0 +1,98 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::storage::SeekSource;
+use self::fountaincode::types::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::io::Cursor;
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+#[test]
+fn seed_same_seed_same_stream() {
+    let data = random_data(10_000);
+    for enc_type in &[EncoderType::Systematic, EncoderType::Random] {
+        let mut a = IdealEncoder::with_seed(data.clone(), 100, enc_type.clone(), 42);
+        let mut b = IdealEncoder::with_seed(data.clone(), 100, enc_type.clone(), 42);
+        let mut c = IdealEncoder::with_seed(data.clone(), 100, enc_type.clone(), 43);
+        let stream_a: Vec<_> = (0..1000).map(|_| a.next()).collect();
+        let stream_b: Vec<_> = (0..1000).map(|_| b.next()).collect();
+        let stream_c: Vec<_> = (0..1000).map(|_| c.next()).collect();
+        assert_eq!(stream_a, stream_b);
+        assert_ne!(stream_a, stream_c);
+
+        let mut a =
+            RobustEncoder::with_seed(data.clone(), 100, enc_type.clone(), 0.2, None, 0.05, 7);
+        let mut b =
+            RobustEncoder::with_seed(data.clone(), 100, enc_type.clone(), 0.2, None, 0.05, 7);
+        for _ in 0..1000 {
+            assert_eq!(a.next(), b.next());
+        }
+    }
+}
+
+#[test]
+fn seed_seek_skips_droplets() {
+    let data = random_data(10_000);
+    for enc_type in &[EncoderType::Systematic, EncoderType::Random] {
+        let mut enc =
+            RobustEncoder::with_seed(data.clone(), 100, enc_type.clone(), 0.2, None, 0.05, 1234);
+        let stream: Vec<_> = (0..500).map(|_| enc.next()).collect();
+        for &n in &[0, 1, 99, 100, 198, 199, 200, 499] {
+            let mut enc = RobustEncoder::with_seed(
+                data.clone(),
+                100,
+                enc_type.clone(),
+                0.2,
+                None,
+                0.05,
+                1234,
+            );
+            enc.seek(n);
+            assert_eq!(
+                stream[n..],
+                (n..500).map(|_| enc.next()).collect::<Vec<_>>()[..]
+            );
+        }
+    }
+}
+
+#[test]
+fn seed_reader_matches_memory() {
+    let data = random_data(12_345);
+    let mut mem = IdealEncoder::with_seed(data.clone(), 64, EncoderType::Systematic, 99);
+    let (source, len) = SeekSource::new(Cursor::new(data.clone())).unwrap();
+    let mut reader = IdealEncoder::with_source(source, len, 64, EncoderType::Systematic, 99);
+    for _ in 0..1000 {
+        assert_eq!(mem.next(), reader.next());
+    }
+}
+
+#[test]
+fn seed_decode_from_two_senders() {
+    // two senders of the same stream, each sends every other droplet
+    let data = random_data(20_000);
+    let mut even = IdealEncoder::with_seed(data.clone(), 100, EncoderType::Random, 5);
+    let mut odd = IdealEncoder::with_seed(data.clone(), 100, EncoderType::Random, 5);
+    odd.seek(1);
+    let mut dec = Decoder::new(data.len(), 100);
+    for i in 0.. {
+        let enc = if i % 2 == 0 { &mut even } else { &mut odd };
+        let drop = enc.next();
+        let cnt = enc.cnt;
+        enc.seek(cnt + 1);
+        if let CatchResult::Finished(result, stats) = dec.catch(drop).unwrap() {
+            assert_eq!(data, result);
+            assert_eq!(stats.cnt_droplets, i + 1);
+            return;
+        }
+    }
+}
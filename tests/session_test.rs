@@ -0,0 +1,215 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::session::Session;
+use self::fountaincode::types::*;
+use self::fountaincode::wire::{Limits, ObjectParams, WireError};
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+#[test]
+fn session_interleaved_objects() {
+    let mut objects = HashMap::new();
+    let mut senders = Vec::new();
+    for id in 0..10u64 {
+        let data = random_data(1000 + 500 * id as usize);
+        let blocksize = 16 + id as usize * 8;
+        let params = ObjectParams::new(data.len(), blocksize).with_object_id(id * 1000);
+        senders.push((
+            params,
+            RobustEncoder::new(
+                data.clone(),
+                blocksize,
+                EncoderType::Random,
+                0.2,
+                None,
+                0.05,
+            ),
+        ));
+        objects.insert(id * 1000, data);
+    }
+
+    let mut session = Session::new(Duration::from_secs(60));
+    let mut loss_rng = thread_rng();
+    while !objects.is_empty() {
+        for (params, enc) in senders.iter_mut() {
+            let packet = enc.next().to_bytes(params);
+            if loss_rng.gen::<f32>() < 0.2 {
+                continue;
+            }
+            match session.receive(&packet).unwrap() {
+                Some((id, CatchResult::Finished(data, _))) => {
+                    assert_eq!(objects.remove(&id).unwrap(), data);
+                }
+                Some((id, CatchResult::Missing(_))) => assert!(objects.contains_key(&id)),
+                Some((_, CatchResult::DigestMismatch(_))) => unreachable!("no digest was set"),
+                None => assert!(!objects.contains_key(&params.object_id)),
+            }
+        }
+        assert!(session.cnt_active() <= objects.len());
+    }
+}
+
+#[test]
+fn session_ignores_completed_and_mismatching_droplets() {
+    let data = random_data(1000);
+    let params = ObjectParams::new(1000, 100).with_object_id(7);
+    let mut enc = IdealEncoder::new(data.clone(), 100, EncoderType::Systematic);
+    let mut session = Session::new(Duration::from_secs(60));
+
+    let first = enc.next();
+    assert!(matches!(
+        session.catch(params, first.clone()),
+        Some(CatchResult::Missing(_))
+    ));
+    // same object id, but a different length
+    let other = ObjectParams::new(2000, 100).with_object_id(7);
+    assert!(session.catch(other, first).is_none());
+    // raptorq symbols are not routed
+    let esi = Droplet::new(DropType::Esi(1), vec![0; 100]);
+    assert!(session.catch(params, esi).is_none());
+
+    loop {
+        if let Some(CatchResult::Finished(result, _)) = session.catch(params, enc.next()) {
+            assert_eq!(data, result);
+            break;
+        }
+    }
+    assert_eq!(session.cnt_active(), 0);
+    assert!(session.catch(params, enc.next()).is_none());
+    assert_eq!(session.cnt_active(), 0);
+}
+
+#[test]
+fn session_evicts_stale_objects() {
+    let data = random_data(1000);
+    let mut session = Session::new(Duration::from_secs(5));
+    let stale = ObjectParams::new(1000, 100).with_object_id(1);
+    let done = ObjectParams::new(1000, 100).with_object_id(2);
+
+    let mut enc = IdealEncoder::new(data.clone(), 100, EncoderType::Systematic);
+    session.catch(stale, enc.next()).unwrap();
+    let mut enc = IdealEncoder::new(data, 100, EncoderType::Systematic);
+    while let Some(CatchResult::Missing(_)) = session.catch(done, enc.next()) {}
+    assert_eq!(session.cnt_active(), 1);
+
+    assert!(session.evict_stale(Instant::now()).is_empty());
+    assert_eq!(session.cnt_active(), 1);
+    let later = Instant::now() + Duration::from_secs(6);
+    assert_eq!(session.evict_stale(later), vec![1]);
+    assert_eq!(session.cnt_active(), 0);
+
+    // the completed object was forgotten as well, its id can be used again
+    assert!(matches!(
+        session.catch(done, enc.next()),
+        Some(CatchResult::Missing(_))
+    ));
+}
+
+#[test]
+fn session_rejects_garbage() {
+    let mut session = Session::new(Duration::from_secs(5));
+    assert_eq!(
+        session.receive(&[1, 2, 3]).unwrap_err(),
+        WireError::Truncated {
+            needed: 40,
+            available: 3
+        }
+    );
+    let empty = Droplet::new(DropType::Seeded(1, 1), vec![0; 16]);
+    let packet = empty.to_bytes(&ObjectParams::new(0, 16));
+    assert!(session.receive(&packet).unwrap().is_none());
+    assert_eq!(session.cnt_active(), 0);
+}
+
+#[test]
+fn session_rejects_oversized_objects() {
+    let mut session = Session::new(Duration::from_secs(5));
+    // a single forged header must not make the session allocate the object
+    let drop = Droplet::new(DropType::Seeded(1, 1), vec![0; 16]);
+    let packet = drop.to_bytes(&ObjectParams::new(1 << 50, 16));
+    assert!(matches!(
+        session.receive(&packet),
+        Err(WireError::ObjectTooLarge { .. })
+    ));
+    assert!(session
+        .catch(ObjectParams::new(usize::MAX, 16), drop.clone())
+        .is_none());
+    assert_eq!(session.cnt_active(), 0);
+
+    session.set_limits(Limits {
+        max_object_size: 2000,
+        max_blocks: 50,
+        ..Limits::default()
+    });
+    let params = ObjectParams::new(3000, 100);
+    let packet = Droplet::new(DropType::Edges(0), vec![0; 100]).to_bytes(&params);
+    assert_eq!(
+        session.receive(&packet).unwrap_err(),
+        WireError::ObjectTooLarge {
+            len: 3000,
+            cnt_blocks: 30
+        }
+    );
+    let params = ObjectParams::new(1000, 16);
+    assert!(session.catch(params, drop).is_none());
+    assert_eq!(session.cnt_active(), 0);
+
+    // objects within the limits are decoded
+    let data = random_data(2000);
+    let params = ObjectParams::new(2000, 40);
+    let mut enc = IdealEncoder::new(data.clone(), 40, EncoderType::Systematic);
+    loop {
+        let packet = enc.next().to_bytes(&params);
+        if let Some((_, CatchResult::Finished(decoded, _))) = session.receive(&packet).unwrap() {
+            assert_eq!(decoded, data);
+            break;
+        }
+    }
+}
+
+#[test]
+fn session_bounds_the_concurrent_objects() {
+    let mut session = Session::new(Duration::from_secs(5));
+    session.set_limits(Limits {
+        max_total_size: 10_000,
+        ..Limits::default()
+    });
+    // every forged id is a new object, only 10 of them fit into the budget
+    let drop = Droplet::new(DropType::Seeded(1, 2), vec![0; 100]);
+    for id in 0..1000 {
+        let params = ObjectParams::new(1000, 100).with_object_id(id);
+        let caught = session.catch(params, drop.clone());
+        assert_eq!(caught.is_some(), id < 10);
+    }
+    assert_eq!(session.cnt_active(), 10);
+
+    // the objects being decoded still receive droplets
+    let params = ObjectParams::new(1000, 100).with_object_id(3);
+    assert!(session.catch(params, drop.clone()).is_some());
+
+    // evicting the objects frees the budget
+    let later = Instant::now() + Duration::from_secs(10);
+    assert_eq!(session.evict_stale(later).len(), 10);
+    let data = random_data(1000);
+    let params = ObjectParams::new(1000, 100).with_object_id(2000);
+    let mut enc = IdealEncoder::new(data.clone(), 100, EncoderType::Systematic);
+    loop {
+        if let Some(CatchResult::Finished(decoded, _)) = session.catch(params, enc.next()) {
+            assert_eq!(decoded, data);
+            break;
+        }
+    }
+    assert_eq!(session.cnt_active(), 0);
+}
@@ -4,6 +4,7 @@ extern crate rand;
 use self::fountaincode::ideal_soliton::IdealSoliton;
 use self::fountaincode::robust_soliton::RobustSoliton;
 use self::fountaincode::soliton::Soliton;
+use proptest::prelude::*;
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 #[test]
@@ -46,3 +47,106 @@ fn soliton_compare_test() {
     println!("r_sum2: {:?}", r_sum2);
     assert!(r_sum > i_sum)
 }
+
+/// Upper critical value of the chi-square distribution with `df` degrees of
+/// freedom at significance 0.001, Wilson-Hilferty approximation
+fn chi_square_critical(df: usize) -> f64 {
+    let z = 3.09;
+    let df = df as f64;
+    let a = 2.0 / (9.0 * df);
+    df * (1.0 - a + z * a.sqrt()).powi(3)
+}
+
+/// Samples `cnt` degrees and compares the histogram against `pmf`.
+/// Degrees with few expected samples are pooled into one bin.
+fn chi_square<S: Soliton>(sol: &mut S, k: usize, pmf: impl Fn(usize) -> f64, cnt: usize) {
+    let mut observed = vec![0usize; k + 1];
+    for _ in 0..cnt {
+        let degree = sol.next();
+        assert!((1..=k).contains(&degree), "degree {} of {}", degree, k);
+        observed[degree] += 1;
+    }
+
+    let mut stat = 0.0;
+    let mut df = 0;
+    let (mut pooled_expected, mut pooled_observed) = (0.0, 0);
+    for (d, &seen) in observed.iter().enumerate().skip(1) {
+        let expected = pmf(d) * cnt as f64;
+        if expected < 5.0 {
+            pooled_expected += expected;
+            pooled_observed += seen;
+            continue;
+        }
+        stat += (seen as f64 - expected).powi(2) / expected;
+        df += 1;
+    }
+    if pooled_expected >= 5.0 {
+        stat += (pooled_observed as f64 - pooled_expected).powi(2) / pooled_expected;
+        df += 1;
+    } else {
+        assert!(pooled_observed as f64 <= pooled_expected + 10.0);
+    }
+    let df = df - 1;
+    assert!(
+        stat < chi_square_critical(df),
+        "chi-square {} with {} degrees of freedom",
+        stat,
+        df
+    );
+}
+
+#[test]
+fn robust_soliton_pmf_is_normalized() {
+    for &k in &[1, 2, 5, 10, 100, 1000, 10_000] {
+        let sol = RobustSoliton::new(k, 0, 0.1, None, 0.05);
+        let sum: f64 = (1..=k).map(|d| sol.pmf(d)).sum();
+        assert!((sum - 1.0).abs() < 1e-9, "k = {}, sum = {}", k, sum);
+        assert!((1..=k).all(|d| sol.pmf(d) >= 0.0));
+        assert_eq!(sol.pmf(0), 0.0);
+        assert_eq!(sol.pmf(k + 1), 0.0);
+    }
+}
+
+#[test]
+fn robust_soliton_has_degree_one() {
+    // tau was always 0 for degrees below the spike, which starved the decoder
+    let sol = RobustSoliton::new(100, 0, 0.1, None, 0.05);
+    assert!(sol.pmf(1) > 1.0 / 100.0);
+    assert!(sol.pmf(sol.spike()) > sol.pmf(sol.spike() - 1));
+}
+
+#[test]
+fn robust_soliton_chi_square() {
+    for &(k, c, spike) in &[
+        (10, 0.1, None),
+        (100, 0.1, None),
+        (100, 0.2, Some(40)),
+        (1000, 0.05, None),
+    ] {
+        let mut sol = RobustSoliton::new(k, 17, c, spike, 0.05);
+        let reference = sol.clone();
+        chi_square(&mut sol, k, |d| reference.pmf(d), 200_000);
+    }
+}
+
+#[test]
+fn ideal_soliton_chi_square() {
+    let k = 100;
+    let mut sol = IdealSoliton::new(k, 17);
+    let pmf = |d: usize| {
+        if d == 1 {
+            1.0 / k as f64
+        } else {
+            1.0 / (d * (d - 1)) as f64
+        }
+    };
+    chi_square(&mut sol, k, pmf, 200_000);
+}
+
+proptest! {
+    #[test]
+    fn robust_soliton_degree_in_range(k in 1..200usize, u in 0.0..1.0f64) {
+        let sol = RobustSoliton::new(k, 0, 0.1, None, 0.05);
+        prop_assert!((1..=k).contains(&sol.degree(u)));
+    }
+}
@@ -0,0 +1,121 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::lt_encoder::get_sample_from_rng_by_seed;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::storage::BlockSource;
+use self::fountaincode::types::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::fs;
+use std::io::{self, Cursor};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+/// Computes the payload of a droplet from the data in memory
+fn expected_payload(drop: &Droplet, data: &[u8], blocksize: usize) -> Vec<u8> {
+    let cnt_blocks = data.len().div_ceil(blocksize);
+    let edges: Vec<usize> = match drop.droptype {
+        DropType::Seeded(seed, degree) => {
+            get_sample_from_rng_by_seed(seed, cnt_blocks, degree).collect()
+        }
+        DropType::Edges(idx) => vec![idx],
+        DropType::Esi(_) => unreachable!(),
+    };
+    let mut r = vec![0; blocksize];
+    for idx in edges {
+        let block = &data[idx * blocksize..data.len().min((idx + 1) * blocksize)];
+        for (d, b) in r.iter_mut().zip(block) {
+            *d ^= b;
+        }
+    }
+    r
+}
+
+fn decode<E: Encoder>(enc: &mut E, len: usize, blocksize: usize) -> Vec<u8> {
+    let mut dec = Decoder::new(len, blocksize);
+    loop {
+        if let CatchResult::Finished(data, _) = dec.catch(enc.try_next().unwrap()).unwrap() {
+            return data;
+        }
+    }
+}
+
+#[test]
+fn source_reader_droplets_match_memory() {
+    for &(len, blocksize) in &[(1000, 10), (1234, 100), (1, 16), (5000, 7)] {
+        let data = random_data(len);
+        let mut enc = IdealEncoder::from_reader(
+            Cursor::new(data.clone()),
+            blocksize,
+            EncoderType::Systematic,
+        )
+        .unwrap();
+        for _ in 0..500 {
+            let drop = enc.try_next().unwrap();
+            assert_eq!(drop.data, expected_payload(&drop, &data, blocksize));
+        }
+
+        let mut enc = RobustEncoder::from_reader(
+            Cursor::new(data.clone()),
+            blocksize,
+            EncoderType::Random,
+            0.2,
+            None,
+            0.05,
+        )
+        .unwrap();
+        for _ in 0..500 {
+            let drop = enc.try_next().unwrap();
+            assert_eq!(drop.data, expected_payload(&drop, &data, blocksize));
+        }
+    }
+}
+
+#[test]
+fn source_reader_decodes() {
+    for size in 1000..1010 {
+        for chunk in 10..20 {
+            let data = random_data(size);
+            let mut enc =
+                IdealEncoder::from_reader(Cursor::new(data.clone()), chunk, EncoderType::Random)
+                    .unwrap();
+            assert_eq!(data, decode(&mut enc, size, chunk));
+        }
+    }
+}
+
+#[test]
+fn source_file() {
+    let data = random_data(100_000);
+    let path = std::env::temp_dir().join(format!("fountain-source-{}", thread_rng().gen::<u64>()));
+    fs::write(&path, &data).unwrap();
+    let file = fs::File::open(&path).unwrap();
+    let mut enc =
+        RobustEncoder::from_reader(file, 1024, EncoderType::Systematic, 0.2, None, 0.05).unwrap();
+    let decoded = decode(&mut enc, data.len(), 1024);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(data, decoded);
+}
+
+struct FailingSource;
+
+impl BlockSource for FailingSource {
+    fn read_at(&mut self, _offset: u64, _buf: &mut [u8]) -> io::Result<()> {
+        Err(io::Error::other("device gone"))
+    }
+}
+
+#[test]
+fn source_errors_are_returned() {
+    let mut enc = IdealEncoder::with_source(FailingSource, 1000, 100, EncoderType::Random, 0);
+    let err = enc.try_next().unwrap_err();
+    assert_eq!(err.to_string(), "device gone");
+}
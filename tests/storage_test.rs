@@ -0,0 +1,124 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::{DecodeError, Decoder};
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::robust_encoder::RobustEncoder;
+use self::fountaincode::storage::{BlockStore, SeekStore};
+use self::fountaincode::types::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Cursor};
+
+fn random_data(len: usize) -> Vec<u8> {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(len).collect();
+    s.into_bytes()
+}
+
+fn decode_into<S: BlockStore>(buf: &[u8], chunk_len: usize, mode: DecoderMode, store: S) -> S {
+    let mut enc = RobustEncoder::new(
+        buf.to_vec(),
+        chunk_len,
+        EncoderType::Random,
+        0.2,
+        None,
+        0.05,
+    );
+    let mut dec = Decoder::with_store(buf.len(), chunk_len, mode, store);
+    let mut loss_rng = thread_rng();
+    while !dec.is_finished() {
+        let drop = enc.next();
+        if loss_rng.gen::<f32>() > 0.3 {
+            dec.try_catch(drop).unwrap();
+        }
+    }
+    dec.into_inner()
+}
+
+#[test]
+fn storage_vec_without_copy() {
+    let buf = random_data(10_000);
+    let data = decode_into(&buf, 64, DecoderMode::Peeling, Vec::new());
+    assert_eq!(buf, data);
+}
+
+#[test]
+fn storage_vec_is_moved_into_the_result() {
+    let buf = random_data(10_000);
+    let mut enc = RobustEncoder::new(buf.clone(), 64, EncoderType::Systematic, 0.2, None, 0.05);
+    let mut dec = Decoder::new(buf.len(), 64);
+    loop {
+        if let CatchResult::Finished(data, _) = dec.catch(enc.next()).unwrap() {
+            assert_eq!(buf, data);
+            break;
+        }
+    }
+    // the decoder no longer holds the object
+    assert!(matches!(
+        dec.catch(enc.next()),
+        Err(DecodeError::AlreadyFinished)
+    ));
+    assert!(dec.into_inner().is_empty());
+}
+
+#[test]
+fn storage_cursor_uneven_sizes() {
+    for size in 1000..1020 {
+        for chunk in 10..20 {
+            for mode in &[DecoderMode::Peeling, DecoderMode::Elimination] {
+                let buf = random_data(size);
+                let store = SeekStore::new(Cursor::new(Vec::new()));
+                let data = decode_into(&buf, chunk, *mode, store);
+                assert_eq!(buf, data.into_inner().into_inner());
+            }
+        }
+    }
+}
+
+#[test]
+fn storage_slice() {
+    let buf = random_data(5_000);
+    let mut out = vec![0; 5_000];
+    decode_into(&buf, 100, DecoderMode::Elimination, &mut out[..]);
+    assert_eq!(buf, out);
+}
+
+#[test]
+fn storage_file() {
+    let buf = random_data(100_000);
+    let path = std::env::temp_dir().join(format!("fountain-storage-{}", thread_rng().gen::<u64>()));
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create_new(true)
+        .open(&path)
+        .unwrap();
+    decode_into(&buf, 1024, DecoderMode::Peeling, SeekStore::new(file));
+    let written = fs::read(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(buf, written);
+}
+
+struct FailingStore;
+
+impl BlockStore for FailingStore {
+    fn write_at(&mut self, _offset: u64, _data: &[u8]) -> io::Result<()> {
+        Err(io::Error::other("disk full"))
+    }
+
+    fn read_at(&mut self, _offset: u64, _buf: &mut [u8]) -> io::Result<()> {
+        unreachable!()
+    }
+}
+
+#[test]
+fn storage_errors_are_returned() {
+    let buf = random_data(1_000);
+    let mut enc = RobustEncoder::new(buf, 100, EncoderType::Systematic, 0.2, None, 0.05);
+    let mut dec = Decoder::with_store(1_000, 100, DecoderMode::Peeling, FailingStore);
+    match dec.try_catch(enc.next()) {
+        Err(DecodeError::Io(err)) => assert_eq!(err.to_string(), "disk full"),
+        other => panic!("expected a storage error, got {:?}", other),
+    }
+}
@@ -0,0 +1,197 @@
+extern crate fountaincode;
+extern crate rand;
+
+use self::fountaincode::decoder::Decoder;
+use self::fountaincode::droplet::Droplet;
+use self::fountaincode::encoder::Encoder;
+use self::fountaincode::ideal_encoder::IdealEncoder;
+use self::fountaincode::types::*;
+use self::fountaincode::wire::{ObjectParams, WireError, HEADER_LEN};
+use proptest::prelude::*;
+use rand::distributions::Alphanumeric;
+use rand::{thread_rng, Rng};
+
+#[test]
+fn wire_roundtrip_seeded() {
+    let params = ObjectParams::new(1000, 64);
+    let drop = Droplet::new(DropType::Seeded(0xdead_beef_cafe, 7), vec![42; 64]);
+    let bytes = drop.to_bytes(&params);
+    assert_eq!(bytes.len(), HEADER_LEN + 64);
+
+    let (parsed, parsed_params) = Droplet::from_bytes(&bytes).unwrap();
+    assert_eq!(drop, parsed);
+    assert_eq!(params, parsed_params);
+}
+
+#[test]
+fn wire_roundtrip_edges() {
+    let params = ObjectParams::new(1000, 64);
+    let drop = Droplet::new(DropType::Edges(15), vec![7; 64]);
+    let bytes = drop.to_bytes(&params);
+
+    let (parsed, parsed_params) = Droplet::from_bytes(&bytes).unwrap();
+    assert_eq!(drop, parsed);
+    assert_eq!(params, parsed_params);
+}
+
+#[test]
+fn wire_roundtrip_esi() {
+    let params = ObjectParams::new(1000, 64);
+    let drop = Droplet::new(DropType::Esi(70_000), vec![3; 64]);
+    let bytes = drop.to_bytes(&params);
+
+    let (parsed, parsed_params) = Droplet::from_bytes(&bytes).unwrap();
+    assert_eq!(drop, parsed);
+    assert_eq!(params, parsed_params);
+
+    let mut too_large = bytes;
+    too_large[4] = 1;
+    assert_eq!(Droplet::from_bytes(&too_large), Err(WireError::Overflow));
+}
+
+#[test]
+fn wire_rejects_malformed_packets() {
+    let params = ObjectParams::new(1000, 64);
+    let bytes = Droplet::new(DropType::Seeded(1, 3), vec![0; 64]).to_bytes(&params);
+
+    assert_eq!(
+        Droplet::from_bytes(&bytes[..10]),
+        Err(WireError::Truncated {
+            needed: HEADER_LEN,
+            available: 10
+        })
+    );
+    assert_eq!(
+        Droplet::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(WireError::Truncated {
+            needed: HEADER_LEN + 64,
+            available: HEADER_LEN + 63
+        })
+    );
+
+    let mut longer = bytes.clone();
+    longer.push(0);
+    assert_eq!(
+        Droplet::from_bytes(&longer),
+        Err(WireError::TrailingBytes(1))
+    );
+
+    let mut version = bytes.clone();
+    version[0] = 99;
+    assert_eq!(
+        Droplet::from_bytes(&version),
+        Err(WireError::UnsupportedVersion(99))
+    );
+
+    let mut kind = bytes.clone();
+    kind[1] = 5;
+    assert_eq!(
+        Droplet::from_bytes(&kind),
+        Err(WireError::UnknownDropType(5))
+    );
+
+    let mut flags = bytes.clone();
+    flags[3] = 4;
+    assert_eq!(
+        Droplet::from_bytes(&flags),
+        Err(WireError::UnsupportedFlags(4))
+    );
+
+    let mut degree = bytes.clone();
+    degree[12..16].copy_from_slice(&[0; 4]);
+    assert_eq!(Droplet::from_bytes(&degree), Err(WireError::InvalidDegree));
+
+    let mut blocksize = bytes;
+    blocksize[24..28].copy_from_slice(&[0; 4]);
+    assert_eq!(
+        Droplet::from_bytes(&blocksize),
+        Err(WireError::InvalidBlocksize)
+    );
+
+    let out_of_range =
+        Droplet::new(DropType::Edges(16), vec![0; 64]).to_bytes(&ObjectParams::new(1000, 64));
+    assert_eq!(
+        Droplet::from_bytes(&out_of_range),
+        Err(WireError::BlockOutOfRange {
+            index: 16,
+            cnt_blocks: 16
+        })
+    );
+
+    let short_payload =
+        Droplet::new(DropType::Edges(1), vec![0; 32]).to_bytes(&ObjectParams::new(1000, 64));
+    assert_eq!(
+        Droplet::from_bytes(&short_payload),
+        Err(WireError::PayloadLength {
+            expected: 64,
+            actual: 32
+        })
+    );
+}
+
+#[test]
+fn wire_decode_over_packets() {
+    let s: String = thread_rng().sample_iter(Alphanumeric).take(3000).collect();
+    let buf = s.into_bytes();
+    let to_compare = buf.clone();
+
+    let params = ObjectParams::new(buf.len(), 100);
+    let mut enc = IdealEncoder::new(buf, params.blocksize, EncoderType::Systematic);
+    let mut dec: Option<Decoder> = None;
+
+    loop {
+        let packet = enc.next().to_bytes(&params);
+        let (drop, rx_params) = Droplet::from_bytes(&packet).unwrap();
+        let dec = dec.get_or_insert_with(|| Decoder::new(rx_params.len, rx_params.blocksize));
+        match dec.catch(drop).unwrap() {
+            CatchResult::Missing(_stats) => {}
+            CatchResult::Finished(data, _stats) => {
+                assert_eq!(to_compare, data);
+                return;
+            }
+            CatchResult::DigestMismatch(_) => unreachable!("no digest was set"),
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10000))]
+    #[test]
+    fn wire_garbage_prop_test(bytes in proptest::collection::vec(any::<u8>(), 0..256)) {
+        // must never panic, and whatever parses has to serialize to the same bytes
+        if let Ok((drop, params)) = Droplet::from_bytes(&bytes) {
+            prop_assert_eq!(drop.to_bytes(&params), bytes);
+        }
+    }
+
+    #[test]
+    fn wire_mutated_header_prop_test(pos in 0..HEADER_LEN, val in any::<u8>(), seed in any::<u64>()) {
+        let params = ObjectParams::new(500, 16);
+        let mut bytes = Droplet::new(DropType::Seeded(seed, 4), vec![1; 16]).to_bytes(&params);
+        bytes[pos] = val;
+        if let Ok((drop, params)) = Droplet::from_bytes(&bytes) {
+            prop_assert_eq!(drop.to_bytes(&params), bytes);
+        }
+    }
+
+    #[test]
+    fn wire_roundtrip_prop_test(
+        seed in any::<u64>(),
+        degree in 1usize..1000,
+        len in 1usize..100_000,
+        blocksize in 1usize..512,
+        seeded in any::<bool>(),
+        object_id in any::<u64>(),
+    ) {
+        let params = ObjectParams::new(len, blocksize).with_object_id(object_id);
+        let droptype = if seeded {
+            DropType::Seeded(seed, degree)
+        } else {
+            DropType::Edges(seed as usize % params.cnt_blocks())
+        };
+        let drop = Droplet::new(droptype, vec![0xa5; blocksize]);
+        let (parsed, parsed_params) = Droplet::from_bytes(&drop.to_bytes(&params)).unwrap();
+        prop_assert_eq!(drop, parsed);
+        prop_assert_eq!(params, parsed_params);
+    }
+}
@@ -0,0 +1,34 @@
+extern crate fountaincode;
+
+use self::fountaincode::xor::{xor_into, Kernel};
+use proptest::prelude::*;
+
+#[test]
+fn detected_kernel_is_supported() {
+    assert!(Kernel::detect().is_supported());
+    assert!(Kernel::Bytes.is_supported());
+    assert!(Kernel::Words.is_supported());
+}
+
+#[test]
+#[should_panic(expected = "blocks of different length")]
+fn lengths_must_match() {
+    xor_into(&mut [0; 4], &[0; 5]);
+}
+
+proptest! {
+    #[test]
+    fn kernels_agree(pairs in prop::collection::vec(any::<(u8, u8)>(), 0..300), offset in 0usize..32) {
+        // the offset misaligns the blocks
+        let offset = offset.min(pairs.len());
+        let (dst, src): (Vec<u8>, Vec<u8>) = pairs.into_iter().unzip();
+        let expected: Vec<u8> = dst.iter().zip(src.iter()).map(|(d, s)| d ^ s).collect();
+
+        for kernel in Kernel::ALL.iter().filter(|kernel| kernel.is_supported()) {
+            let mut actual = dst.clone();
+            kernel.xor_into(&mut actual[offset..], &src[offset..]);
+            prop_assert_eq!(&actual[offset..], &expected[offset..], "{:?}", kernel);
+            prop_assert_eq!(&actual[..offset], &dst[..offset], "{:?}", kernel);
+        }
+    }
+}